@@ -1,4 +1,7 @@
+use std::mem::MaybeUninit;
+use std::process;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
@@ -47,5 +50,113 @@ clone!(
     |len| SharedHybridRc::from_str_ref(&*"x".repeat(len))
 );
 
-criterion_group!(benches, clone);
+// N individual clones vs. a single `scatter` into N slots
+fn scatter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Clone - Scatter");
+    let counts = vec![1usize, 4, 16, 64];
+
+    for count in counts {
+        group.bench_function(BenchmarkId::new("SharedRc clone", count), |b| {
+            let s = SharedRc::from_str_ref("x");
+            let mut clones = Vec::with_capacity(count);
+            b.iter(|| {
+                clones.clear();
+                clones.extend((0..count).map(|_| s.clone()));
+                black_box(&clones);
+            })
+        });
+
+        group.bench_function(BenchmarkId::new("SharedRc scatter", count), |b| {
+            let s = SharedRc::from_str_ref("x");
+            let mut slots: Vec<MaybeUninit<SharedRc<[u8]>>> = Vec::with_capacity(count);
+            b.iter(|| {
+                slots.clear();
+                slots.resize_with(count, MaybeUninit::uninit);
+                let clones = s.scatter(&mut slots);
+                black_box(&clones);
+                // SAFETY: Each clone is dropped once and the slots are cleared before reuse
+                unsafe { std::ptr::drop_in_place(clones) };
+            })
+        });
+    }
+
+    group.finish();
+}
+
+const MAX_COUNT: usize = usize::MAX >> 1;
+
+#[cold]
+#[inline(never)]
+fn cold() {}
+
+// Same stable hint `SharedMeta::clone` uses for its overflow check
+#[inline(always)]
+fn unlikely(b: bool) -> bool {
+    if b {
+        cold()
+    }
+    b
+}
+
+// The cost of the overflow check on the shared clone `fetch_add`: no check, a plain check (before
+// the hint was added), and the check behind `unlikely` (what `SharedRc` does now)
+fn overflow_check(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Clone - Overflow check");
+
+    group.bench_function("fetch_add unchecked", |b| {
+        let count = AtomicUsize::new(1);
+        b.iter(|| {
+            for _ in 0..ITERATIONS {
+                black_box(count.fetch_add(1, Ordering::Relaxed));
+                count.fetch_sub(1, Ordering::Release);
+            }
+        })
+    });
+
+    group.bench_function("fetch_add checked", |b| {
+        let count = AtomicUsize::new(1);
+        b.iter(|| {
+            for _ in 0..ITERATIONS {
+                if black_box(count.fetch_add(1, Ordering::Relaxed)) > MAX_COUNT {
+                    process::abort()
+                }
+                count.fetch_sub(1, Ordering::Release);
+            }
+        })
+    });
+
+    group.bench_function("fetch_add checked unlikely", |b| {
+        let count = AtomicUsize::new(1);
+        b.iter(|| {
+            for _ in 0..ITERATIONS {
+                if unlikely(black_box(count.fetch_add(1, Ordering::Relaxed)) > MAX_COUNT) {
+                    process::abort()
+                }
+                count.fetch_sub(1, Ordering::Release);
+            }
+        })
+    });
+
+    group.bench_function("SharedRc", |b| {
+        let s = SharedRc::new(0u8);
+        b.iter(|| {
+            for _ in 0..ITERATIONS {
+                black_box(s.clone());
+            }
+        })
+    });
+
+    group.bench_function("Arc", |b| {
+        let s = Arc::new(0u8);
+        b.iter(|| {
+            for _ in 0..ITERATIONS {
+                black_box(s.clone());
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, clone, scatter, overflow_check);
 criterion_main!(benches);
@@ -0,0 +1,69 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use flexrc::{LocalHybridRc, LocalRc, SharedHybridRc, SharedRc};
+
+// Converts to the other handle type, copying the data only if the cheap conversion isn't possible
+macro_rules! into_other_or_copy {
+    ($rc:expr, $other:ty) => {
+        match $rc.try_into_other() {
+            Ok(other) => other,
+            Err(this) => <$other>::from_str_ref(&*this),
+        }
+    };
+}
+
+macro_rules! convert {
+    ($($name:expr, $setup:expr, $op:expr),+) => {
+        fn convert(c: &mut Criterion) {
+            let mut group = c.benchmark_group("Convert - Computed");
+            let lengths = vec![0usize, 10, 20, 100, 1000, 16384];
+
+            for len in lengths {
+                $(let id = BenchmarkId::new($name, len);
+                group.bench_function(id, |b| {
+                    b.iter_batched($setup(len), |s| {
+                        let other = $op(s);
+                        black_box(&other);
+                        other
+                    }, BatchSize::SmallInput)
+                });)+
+            }
+
+            group.finish();
+        }
+    };
+}
+
+convert!(
+    "LocalRc -> SharedRc (unique)",
+    |len| move || LocalRc::from_str_ref(&*"x".repeat(len)),
+    |s: LocalRc<[u8]>| into_other_or_copy!(s, SharedRc<[u8]>),
+    "LocalRc -> SharedRc (not unique)",
+    |len| move || {
+        let s = LocalRc::from_str_ref(&*"x".repeat(len));
+        (s.clone(), s)
+    },
+    |(s, _keep): (LocalRc<[u8]>, LocalRc<[u8]>)| into_other_or_copy!(s, SharedRc<[u8]>),
+    "SharedRc -> LocalRc (unique)",
+    |len| move || SharedRc::from_str_ref(&*"x".repeat(len)),
+    |s: SharedRc<[u8]>| into_other_or_copy!(s, LocalRc<[u8]>),
+    "SharedRc -> LocalRc (not unique)",
+    |len| move || {
+        let s = SharedRc::from_str_ref(&*"x".repeat(len));
+        (s.clone(), s)
+    },
+    |(s, _keep): (SharedRc<[u8]>, SharedRc<[u8]>)| into_other_or_copy!(s, LocalRc<[u8]>),
+    "LocalHybridRc -> SharedHybridRc (unique)",
+    |len| move || LocalHybridRc::from_str_ref(&*"x".repeat(len)),
+    |s: LocalHybridRc<[u8]>| into_other_or_copy!(s, SharedHybridRc<[u8]>),
+    "LocalHybridRc -> SharedHybridRc (not unique)",
+    |len| move || {
+        let s = LocalHybridRc::from_str_ref(&*"x".repeat(len));
+        (s.clone(), s)
+    },
+    |(s, _keep): (LocalHybridRc<[u8]>, LocalHybridRc<[u8]>)| {
+        into_other_or_copy!(s, SharedHybridRc<[u8]>)
+    }
+);
+
+criterion_group!(benches, convert);
+criterion_main!(benches);
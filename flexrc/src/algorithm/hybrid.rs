@@ -1,20 +1,28 @@
 use core::cell::Cell;
 use core::marker::PhantomData;
-use core::sync::atomic;
-#[cfg(feature = "track_threads")]
+use core::mem;
+#[cfg(all(feature = "track_threads", not(loom)))]
 use core::sync::atomic::AtomicUsize;
-use core::sync::atomic::{AtomicU32, Ordering};
+#[cfg(not(loom))]
+use core::sync::atomic::{self, AtomicU32, Ordering};
+
+#[cfg(all(feature = "track_threads", loom))]
+use loom::sync::atomic::AtomicUsize;
+#[cfg(loom)]
+use loom::sync::atomic::{self, AtomicU32, Ordering};
 
-use static_assertions::{assert_eq_align, assert_eq_size, assert_impl_all, assert_not_impl_any};
+use static_assertions::{
+    assert_eq_align, assert_eq_size, assert_impl_all, assert_not_impl_any, const_assert_eq,
+};
 
-use crate::algorithm::abort;
 #[cfg(feature = "track_threads")]
 use crate::algorithm::hybrid_threads::THREAD_ID;
-use crate::{Algorithm, FlexRc, FlexRcInner};
+use crate::algorithm::{abort, unlikely};
+use crate::{Algorithm, ConvertError, FlexRc, FlexRcInner, SharedRc};
 
-#[cfg(not(feature = "track_threads"))]
+#[cfg(all(not(feature = "track_threads"), not(loom)))]
 assert_eq_size!(HybridMeta<LocalMode>, u64);
-#[cfg(not(feature = "track_threads"))]
+#[cfg(all(not(feature = "track_threads"), not(loom)))]
 assert_eq_size!(HybridMeta<SharedMode>, u64);
 
 assert_eq_size!(HybridMeta<LocalMode>, HybridMeta<SharedMode>);
@@ -23,14 +31,34 @@ assert_eq_size!(LocalInner<usize>, SharedInner<usize>);
 assert_eq_align!(LocalInner<usize>, SharedInner<usize>);
 assert_eq_size!(LocalHybridRc<usize>, SharedHybridRc<usize>);
 assert_eq_align!(LocalHybridRc<usize>, SharedHybridRc<usize>);
+// Conversions cast between the inner types for any `T` (including unsized), so the header and the
+// offset of the data must match as well
+assert_eq_size!(LocalInner<()>, SharedInner<()>);
+assert_eq_align!(LocalInner<()>, SharedInner<()>);
+const_assert_eq!(
+    mem::offset_of!(LocalInner<u8>, data),
+    mem::offset_of!(SharedInner<u8>, data)
+);
 
 assert_impl_all!(SharedHybridRc<usize>: Send, Sync);
+assert_impl_all!(SharedHybridRc<[u8]>: Send, Sync);
 assert_not_impl_any!(LocalHybridRc<usize>: Send, Sync);
-
-#[cfg(feature = "track_threads")]
+// `HybridMeta` has no `Send`/`Sync` impls of its own (and `FlexRc` holds a `NonNull` so it never
+// gets them automatically), so these hinge entirely on the `T: Send + Sync` bounds below
+assert_not_impl_any!(HybridMeta<SharedMode>: Sync);
+assert_not_impl_any!(SharedHybridRc<core::cell::Cell<u8>>: Send, Sync);
+assert_not_impl_any!(SharedHybridRc<alloc::rc::Rc<u8>>: Send, Sync);
+#[cfg(feature = "std")]
+assert_impl_all!(SharedHybridRc<std::sync::Mutex<u8>>: Send, Sync);
+
+#[cfg(all(feature = "track_threads", not(feature = "track_threads_lockfree")))]
 const THREAD_ID_LOCKED: usize = (usize::MAX >> 1) + 1;
-#[cfg(feature = "track_threads")]
+#[cfg(all(feature = "track_threads", not(feature = "track_threads_lockfree")))]
 const THREAD_ID_UNLOCKED: usize = usize::MAX >> 1;
+// No thread owns the local side. With `track_threads_lockfree` the owner is cleared when its last
+// local handle drops, so a thread ID only ever names a thread whose local handles are still alive
+#[cfg(feature = "track_threads_lockfree")]
+const NO_OWNER: usize = 0;
 
 // Entire counter is usable for local
 const MAX_LOCAL_COUNT: u32 = u32::MAX;
@@ -41,11 +69,54 @@ const LOCAL_PRESENT: u32 = (u32::MAX >> 1) + 1;
 // All bits set except top
 const CLEAR_LOCAL: u32 = u32::MAX >> 1;
 
+// The local counter can't be read from a shared handle (it isn't atomic), so all local handles
+// are counted as a single reference when the local present bit is set
+#[inline]
+fn shared_strong_count(shared_count: u32) -> usize {
+    let locals = if shared_count & LOCAL_PRESENT != 0 {
+        1
+    } else {
+        0
+    };
+    (shared_count & CLEAR_LOCAL) as usize + locals
+}
+
 pub struct LocalMode;
 pub struct SharedMode;
 
+impl HybridMeta<LocalMode> {
+    /// The highest number of local handles. The clone that would go past it aborts instead
+    pub const MAX_COUNT: usize = MAX_LOCAL_COUNT as usize;
+
+    /// Raw local count, for asserting intermediate counts in tests
+    #[cfg(any(test, loom))]
+    #[cfg_attr(loom, allow(dead_code))]
+    pub(crate) fn raw_count(&self) -> usize {
+        self.local_count.get() as usize
+    }
+}
+
+impl HybridMeta<SharedMode> {
+    /// The highest number of shared handles (all local handles together count as one more). The
+    /// clone that would go past it aborts instead. Since the check happens after the increment,
+    /// racing clones may briefly push the raw counter further, but none of them survive
+    pub const MAX_COUNT: usize = MAX_SHARED_COUNT as usize + 1;
+
+    /// Raw shared count, for asserting intermediate counts in tests. This is the whole counter, so
+    /// the local present bit is included (a `Relaxed` load, so models must establish ordering some
+    /// other way, such as joining threads)
+    #[cfg(any(test, loom))]
+    #[cfg_attr(loom, allow(dead_code))]
+    pub(crate) fn raw_count(&self) -> usize {
+        self.shared_count.load(Ordering::Relaxed) as usize
+    }
+}
+
 #[repr(C)]
 pub struct HybridMeta<MODE> {
+    // The thread owning the local side. With `track_threads` the top bit doubles as a spinlock
+    // around conversions, while `track_threads_lockfree` instead keeps it cleared while no local
+    // handle exists
     #[cfg(feature = "track_threads")]
     thread_id: AtomicUsize,
     local_count: Cell<u32>,
@@ -55,6 +126,31 @@ pub struct HybridMeta<MODE> {
 
 pub type LocalHybridRc<T> = FlexRc<HybridMeta<LocalMode>, HybridMeta<SharedMode>, T>;
 
+impl<T: ?Sized> LocalHybridRc<T> {
+    /// Returns true if any `SharedHybridRc` to this data exists. If so, `get_mut` will fail until
+    /// they are all dropped. This is only a snapshot, as shared handles may be dropped at any time
+    #[inline]
+    pub fn is_shared_elsewhere(&self) -> bool {
+        self.as_inner()
+            .metadata
+            .shared_count
+            .load(Ordering::Acquire)
+            & CLEAR_LOCAL
+            != 0
+    }
+}
+
+impl<T: Clone> LocalHybridRc<T> {
+    /// Publishes the data as a regular `SharedRc` (not a `SharedHybridRc`). The two families have
+    /// different metadata headers, so unlike `into_other` this can never reuse the allocation: the
+    /// data is always cloned into a new one, and this handle is dropped
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn into_shared_rc(self) -> SharedRc<T> {
+        SharedRc::new(T::clone(&self))
+    }
+}
+
 type LocalInner<T> = FlexRcInner<HybridMeta<LocalMode>, HybridMeta<SharedMode>, T>;
 type SharedInner<T> = FlexRcInner<HybridMeta<SharedMode>, HybridMeta<LocalMode>, T>;
 
@@ -77,12 +173,27 @@ impl Algorithm<HybridMeta<LocalMode>, HybridMeta<SharedMode>> for HybridMeta<Loc
         self.local_count.get() == 1 && self.shared_count.load(Ordering::Acquire) == LOCAL_PRESENT
     }
 
+    #[inline]
+    fn strong_count(&self) -> usize {
+        let shared = self.shared_count.load(Ordering::SeqCst) & CLEAR_LOCAL;
+        self.local_count.get() as usize + shared as usize
+    }
+
+    #[inline]
+    fn strong_count_relaxed(&self) -> usize {
+        let shared = self.shared_count.load(Ordering::Relaxed) & CLEAR_LOCAL;
+        self.local_count.get() as usize + shared as usize
+    }
+
     #[inline(always)]
     fn clone(&self) {
         let old = self.local_count.get();
 
-        // TODO: This check adds 15-16% clone overhead - truly needed?
-        if old == MAX_LOCAL_COUNT {
+        // Unlike the `usize` count of `LocalRc` this one is needed: `mem::forget` on clones can
+        // reach `u32::MAX` in seconds, and wrapping would free the data under live handles. The
+        // counter can't be widened without growing the header (and both modes must match). In
+        // the clone benchmark the check is lost in the noise, so it stays unconditional
+        if unlikely(old == MAX_LOCAL_COUNT) {
             abort()
         }
         self.local_count.set(old + 1);
@@ -93,6 +204,12 @@ impl Algorithm<HybridMeta<LocalMode>, HybridMeta<SharedMode>> for HybridMeta<Loc
         self.local_count.set(self.local_count.get() - 1);
 
         if self.local_count.get() == 0 {
+            // Give up ownership while `LOCAL_PRESENT` still keeps the allocation alive. No other
+            // thread can claim it until the bit is cleared below, and the `Release` there
+            // publishes this store along with it
+            #[cfg(feature = "track_threads_lockfree")]
+            self.thread_id.store(NO_OWNER, Ordering::Relaxed);
+
             // FIXME: Verify correct Ordering
             let old = self.shared_count.fetch_and(CLEAR_LOCAL, Ordering::Release);
 
@@ -104,10 +221,34 @@ impl Algorithm<HybridMeta<LocalMode>, HybridMeta<SharedMode>> for HybridMeta<Loc
         }
     }
 
+    #[inline(always)]
+    fn drop_weak(&self) -> bool {
+        // No weak support, so the strong references were the only ones
+        true
+    }
+
+    #[inline]
+    fn can_convert(&self) -> bool {
+        // Local -> shared is always allowed
+        true
+    }
+
     #[inline]
     fn try_into_other<T: ?Sized>(
         &self,
         inner: *mut LocalInner<T>,
+    ) -> Result<*mut SharedInner<T>, *mut LocalInner<T>> {
+        // Take our shared reference first so releasing our local reference can never be the last
+        let inner = self.try_to_other(inner)?;
+        let last = self.drop();
+        debug_assert!(!last);
+        Ok(inner)
+    }
+
+    #[inline]
+    fn try_to_other<T: ?Sized>(
+        &self,
+        inner: *mut LocalInner<T>,
     ) -> Result<*mut SharedInner<T>, *mut LocalInner<T>> {
         // This is always allowed
 
@@ -123,23 +264,62 @@ impl Algorithm<HybridMeta<LocalMode>, HybridMeta<SharedMode>> for HybridMeta<Loc
         }
         Ok(inner)
     }
+}
+
+pub type SharedHybridRc<T> = FlexRc<HybridMeta<SharedMode>, HybridMeta<LocalMode>, T>;
 
+impl<T: ?Sized> SharedHybridRc<T> {
+    /// Returns true if any `LocalHybridRc` to this data exists (on whichever thread owns the local
+    /// side). This is only a snapshot, as the local handles may be dropped at any time
     #[inline]
-    fn try_to_other<T: ?Sized>(
-        &self,
-        inner: *mut LocalInner<T>,
-    ) -> Result<*mut SharedInner<T>, *mut LocalInner<T>> {
-        // Since we can always keep the original, both are the same
-        self.try_into_other(inner)
+    pub fn is_local_present(&self) -> bool {
+        self.as_inner()
+            .metadata
+            .shared_count
+            .load(Ordering::Acquire)
+            & LOCAL_PRESENT
+            != 0
+    }
+
+    /// Converts this into a local handle on this thread. This only fails if a local handle
+    /// already exists (other shared handles don't matter), which is reported as
+    /// `ConvertError::LocalTaken`
+    #[inline]
+    pub fn try_into_local(self) -> Result<LocalHybridRc<T>, ConvertError<Self>> {
+        self.try_into_other().map_err(ConvertError::LocalTaken)
+    }
+
+    /// Returns a new local handle on this thread while keeping this shared one. It succeeds if no
+    /// local handle exists or (with `track_threads`) the existing ones are on this thread
+    #[inline]
+    pub fn try_clone_local(&self) -> Option<LocalHybridRc<T>> {
+        self.try_to_other().ok()
     }
 }
 
-pub type SharedHybridRc<T> = FlexRc<HybridMeta<SharedMode>, HybridMeta<LocalMode>, T>;
+// Same as `try_into_other`, so conversions can be used through the standard trait bounds
+impl<T: ?Sized> TryFrom<SharedHybridRc<T>> for LocalHybridRc<T> {
+    type Error = SharedHybridRc<T>;
+
+    #[inline]
+    fn try_from(rc: SharedHybridRc<T>) -> Result<Self, Self::Error> {
+        rc.try_into_other()
+    }
+}
+
+impl<T: ?Sized> TryFrom<LocalHybridRc<T>> for SharedHybridRc<T> {
+    type Error = LocalHybridRc<T>;
+
+    #[inline]
+    fn try_from(rc: LocalHybridRc<T>) -> Result<Self, Self::Error> {
+        rc.try_into_other()
+    }
+}
 
 // SAFETY: We ensure what we are holding is Sync/Send and we have been careful to ensure invariants
 // that allow these marked to be safe
-unsafe impl<T: Send + Sync> Send for SharedHybridRc<T> {}
-unsafe impl<T: Send + Sync> Sync for SharedHybridRc<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Send for SharedHybridRc<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for SharedHybridRc<T> {}
 
 impl Algorithm<HybridMeta<SharedMode>, HybridMeta<LocalMode>> for HybridMeta<SharedMode> {
     #[inline]
@@ -161,11 +341,22 @@ impl Algorithm<HybridMeta<SharedMode>, HybridMeta<LocalMode>> for HybridMeta<Sha
         self.shared_count.load(Ordering::Acquire) == 1
     }
 
+    #[inline]
+    fn strong_count(&self) -> usize {
+        shared_strong_count(self.shared_count.load(Ordering::SeqCst))
+    }
+
+    #[inline]
+    fn strong_count_relaxed(&self) -> usize {
+        shared_strong_count(self.shared_count.load(Ordering::Relaxed))
+    }
+
     #[inline(always)]
     fn clone(&self) {
         let old = self.shared_count.fetch_add(1, Ordering::Relaxed);
 
-        if old > MAX_SHARED_COUNT {
+        // Mask off the local present bit, else any clone made while a local exists would abort
+        if unlikely(old & CLEAR_LOCAL > MAX_SHARED_COUNT) {
             abort()
         }
     }
@@ -182,11 +373,66 @@ impl Algorithm<HybridMeta<SharedMode>, HybridMeta<LocalMode>> for HybridMeta<Sha
         }
     }
 
-    #[cfg(feature = "track_threads")]
+    #[inline(always)]
+    fn drop_weak(&self) -> bool {
+        // No weak support, so the strong references were the only ones
+        true
+    }
+
+    #[inline]
+    fn can_convert(&self) -> bool {
+        // Only peek at the local present bit (and thread ID) - actually claiming the local slot is
+        // left to `try_into_other`
+        if self.shared_count.load(Ordering::Acquire) < LOCAL_PRESENT {
+            return true;
+        }
+
+        #[cfg(all(feature = "track_threads", not(feature = "track_threads_lockfree")))]
+        {
+            let thread_id = THREAD_ID.with(|thread_id| thread_id.0);
+            self.thread_id.load(Ordering::Acquire) & THREAD_ID_UNLOCKED == thread_id
+        }
+        #[cfg(feature = "track_threads_lockfree")]
+        {
+            let thread_id = THREAD_ID.with(|thread_id| thread_id.0);
+            self.thread_id.load(Ordering::Acquire) == thread_id
+        }
+        #[cfg(not(feature = "track_threads"))]
+        false
+    }
+
     #[inline]
     fn try_into_other<T: ?Sized>(
         &self,
         inner: *mut SharedInner<T>,
+    ) -> Result<*mut LocalInner<T>, *mut SharedInner<T>> {
+        // Fast path: if we are the only handle then no local exists and nobody can race us, so we
+        // can move our reference over to the local counter instead of a clone and drop
+        // Long discussion on why this ordering is required: https://github.com/servo/servo/issues/21186
+        if self.shared_count.load(Ordering::Acquire) == 1 {
+            #[cfg(feature = "track_threads")]
+            self.thread_id
+                .store(THREAD_ID.with(|thread_id| thread_id.0), Ordering::Relaxed);
+            self.local_count.set(1);
+            self.shared_count.store(LOCAL_PRESENT, Ordering::Relaxed);
+
+            // Safety: These are literally the same type - we invented the `SharedMode` and `LocalMode` tags
+            // to FORCE new types where there wouldn't otherwise be so this is safe to cast
+            return Ok(inner as *mut LocalInner<T>);
+        }
+
+        // Take our local reference first so releasing our shared reference can never be the last
+        let inner = self.try_to_other(inner)?;
+        let last = self.drop();
+        debug_assert!(!last);
+        Ok(inner)
+    }
+
+    #[cfg(all(feature = "track_threads", not(feature = "track_threads_lockfree")))]
+    #[inline]
+    fn try_to_other<T: ?Sized>(
+        &self,
+        inner: *mut SharedInner<T>,
     ) -> Result<*mut LocalInner<T>, *mut SharedInner<T>> {
         let thread_id = THREAD_ID.with(|thread_id| thread_id.0);
 
@@ -199,7 +445,10 @@ impl Algorithm<HybridMeta<SharedMode>, HybridMeta<LocalMode>> for HybridMeta<Sha
             if old_thread_id < THREAD_ID_LOCKED {
                 break old_thread_id;
             }
-            std::hint::spin_loop();
+            #[cfg(not(loom))]
+            core::hint::spin_loop();
+            #[cfg(loom)]
+            loom::thread::yield_now();
         };
 
         // Try and make this thread into the local one by setting LOCAL_PRESENT bit.
@@ -232,9 +481,40 @@ impl Algorithm<HybridMeta<SharedMode>, HybridMeta<LocalMode>> for HybridMeta<Sha
         }
     }
 
+    #[cfg(feature = "track_threads_lockfree")]
+    #[inline]
+    fn try_to_other<T: ?Sized>(
+        &self,
+        inner: *mut SharedInner<T>,
+    ) -> Result<*mut LocalInner<T>, *mut SharedInner<T>> {
+        let thread_id = THREAD_ID.with(|thread_id| thread_id.0);
+
+        // Setting the local present bit claims the local side if nobody owns it. If somebody
+        // does, only the owning thread can find its own ID in `thread_id`: no other thread ever
+        // stores it, and the owner clears it before releasing the bit
+        // FIXME: Verify correct Ordering
+        if self.shared_count.fetch_or(LOCAL_PRESENT, Ordering::Acquire) < LOCAL_PRESENT {
+            self.thread_id.store(thread_id, Ordering::Relaxed);
+        } else if self.thread_id.load(Ordering::Relaxed) != thread_id {
+            return Err(inner);
+        }
+
+        // Safety: These are literally the same type - we invented the `SharedMode` and `LocalMode` tags
+        // to FORCE new types where there wouldn't otherwise be so this is safe to cast
+        let inner = inner as *mut LocalInner<T>;
+
+        // Since a) creating a new instance, not reusing b) using a diff ref counter field we now
+        // need to force a clone
+        // SAFETY: See above
+        unsafe {
+            (*inner).metadata.clone();
+        }
+        Ok(inner)
+    }
+
     #[cfg(not(feature = "track_threads"))]
     #[inline]
-    fn try_into_other<T: ?Sized>(
+    fn try_to_other<T: ?Sized>(
         &self,
         inner: *mut SharedInner<T>,
     ) -> Result<*mut LocalInner<T>, *mut SharedInner<T>> {
@@ -259,13 +539,4 @@ impl Algorithm<HybridMeta<SharedMode>, HybridMeta<LocalMode>> for HybridMeta<Sha
             Err(inner)
         }
     }
-
-    #[inline]
-    fn try_to_other<T: ?Sized>(
-        &self,
-        inner: *mut SharedInner<T>,
-    ) -> Result<*mut LocalInner<T>, *mut SharedInner<T>> {
-        // Since we can always keep the original, both are the same
-        self.try_into_other(inner)
-    }
 }
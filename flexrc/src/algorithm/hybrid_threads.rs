@@ -1,46 +1,87 @@
 #![cfg(feature = "track_threads")]
 
+#[cfg(feature = "track_threads_lockfree")]
+use core::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(not(feature = "track_threads_lockfree"))]
 use std::collections::HashSet;
-use std::sync::{Mutex, Once};
+#[cfg(not(feature = "track_threads_lockfree"))]
+use std::sync::{Mutex, OnceLock};
+
+#[cfg(feature = "track_threads_lockfree")]
+use crate::algorithm::{abort, unlikely};
 
 const MAX_THREADS: usize = usize::MAX >> 1;
 
-static mut THREAD_TRACKER: Option<ThreadTracker> = None;
-static ONCE: Once = Once::new();
+#[cfg(not(feature = "track_threads_lockfree"))]
+static THREAD_TRACKER: OnceLock<ThreadTracker> = OnceLock::new();
 
-thread_local! { pub(crate) static THREAD_ID: ThreadId = thread_tracker().unwrap().get_new_id() }
+// Loom runs every model thread on the same OS thread, so it needs its own thread locals
+#[cfg(not(loom))]
+thread_local! { pub(crate) static THREAD_ID: ThreadId = ThreadId::new() }
+#[cfg(loom)]
+loom::thread_local! { pub(crate) static THREAD_ID: ThreadId = ThreadId::new() }
 
 // *** Thread Id ***
 
 pub(crate) struct ThreadId(pub usize);
 
+#[cfg(not(feature = "track_threads_lockfree"))]
+impl ThreadId {
+    #[inline]
+    fn new() -> Self {
+        thread_tracker().get_new_id()
+    }
+}
+
+#[cfg(not(feature = "track_threads_lockfree"))]
 impl Drop for ThreadId {
     fn drop(&mut self) {
-        thread_tracker().unwrap().return_id(self.0);
+        thread_tracker().return_id(self.0);
     }
 }
 
-// *** Thread Tracker ***
+// *** Thread Generation ***
+
+// Generations are never reused, so a thread can't mistake a local handle made by a thread that has
+// since exited for its own. Zero is reserved to mean "no local owner"
+#[cfg(feature = "track_threads_lockfree")]
+static NEXT_GENERATION: AtomicUsize = AtomicUsize::new(1);
 
-fn thread_tracker() -> Option<&'static ThreadTracker> {
-    // SAFETY: This works because THREAD_TRACKER init is synchronized via Once
-    unsafe {
-        ONCE.call_once(|| {
-            THREAD_TRACKER = Some(ThreadTracker::default());
-        });
-        THREAD_TRACKER.as_ref()
+#[cfg(feature = "track_threads_lockfree")]
+impl ThreadId {
+    #[inline]
+    fn new() -> Self {
+        let generation = NEXT_GENERATION.fetch_add(1, Ordering::Relaxed);
+
+        // Like a reference count overflow, running out is only possible on 32-bit targets after
+        // billions of threads, but handing out a generation twice would be unsound
+        if unlikely(generation >= MAX_THREADS) {
+            abort()
+        }
+        ThreadId(generation)
     }
 }
 
+// *** Thread Tracker ***
+
+#[cfg(not(feature = "track_threads_lockfree"))]
+#[inline]
+fn thread_tracker() -> &'static ThreadTracker {
+    THREAD_TRACKER.get_or_init(ThreadTracker::default)
+}
+
+#[cfg(not(feature = "track_threads_lockfree"))]
 #[derive(Default)]
 struct ThreadTrackerInner {
     counter: usize,
     used_counters: HashSet<usize>,
 }
 
+#[cfg(not(feature = "track_threads_lockfree"))]
 #[derive(Default)]
 struct ThreadTracker(Mutex<ThreadTrackerInner>);
 
+#[cfg(not(feature = "track_threads_lockfree"))]
 impl ThreadTracker {
     pub fn get_new_id(&self) -> ThreadId {
         let mut inner = self.0.lock().expect("poisoned lock");
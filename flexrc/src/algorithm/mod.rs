@@ -1,11 +1,13 @@
 mod hybrid;
 #[cfg(feature = "track_threads")]
 mod hybrid_threads;
+mod orderings;
 mod regular;
 
 use crate::FlexRcInner;
 
 pub use hybrid::*;
+pub use orderings::*;
 pub use regular::*;
 
 pub trait Algorithm<META, META2> {
@@ -15,34 +17,96 @@ pub trait Algorithm<META, META2> {
     /// Returns true if this instance is the last one before final release of resources
     fn is_unique(&self) -> bool;
 
+    /// Returns the current strong reference count (precise, `SeqCst` if atomic)
+    fn strong_count(&self) -> usize;
+
+    /// Returns the current strong reference count using a `Relaxed` load if atomic. The value may be
+    /// stale and is only suitable for metrics/logging, never for control flow
+    fn strong_count_relaxed(&self) -> usize;
+
     /// Increment reference counters
     fn clone(&self);
 
-    /// Decrement reference counters and return true if storage should be deallocated
+    /// Increment reference counters by `n` at once. The default simply calls `clone` `n` times
+    #[inline]
+    fn clone_many(&self, n: usize) {
+        for _ in 0..n {
+            self.clone();
+        }
+    }
+
+    /// Decrement reference counters and return true if this was the last strong reference and the
+    /// data should be destroyed
     fn drop(&self) -> bool;
 
-    /// Attempts to converts one inner type into another while consuming the other
+    /// Release the implicit weak reference held collectively by the strong references (once the
+    /// last one drops) or an explicit weak reference, and return true if storage should be
+    /// deallocated. Algorithms without weak support always return true
+    fn drop_weak(&self) -> bool;
+
+    /// Returns true if `try_into_other` would currently succeed. This must be a read-only probe, so
+    /// it may be stale by the time a conversion is attempted if handles exist on other threads
+    fn can_convert(&self) -> bool;
+
+    /// Attempts to converts one inner type into another while consuming the other. On success, the
+    /// reference held by the caller is transferred to the returned inner and must not be dropped
     fn try_into_other<T: ?Sized>(
         &self,
         inner: *mut FlexRcInner<META, META2, T>,
     ) -> Result<*mut FlexRcInner<META2, META, T>, *mut FlexRcInner<META, META2, T>>;
 
-    /// Attempts to converts one inner type into another but NOT consuming the other
+    /// Attempts to converts one inner type into another but NOT consuming the other. On success, a
+    /// new reference is created for the returned inner
     fn try_to_other<T: ?Sized>(
         &self,
         inner: *mut FlexRcInner<META, META2, T>,
     ) -> Result<*mut FlexRcInner<META2, META, T>, *mut FlexRcInner<META, META2, T>>;
 }
 
-#[cfg(feature = "std")]
-#[inline]
+/// Algorithms that additionally support weak references
+#[cfg(feature = "weak")]
+pub trait WeakAlgorithm<META, META2>: Algorithm<META, META2> {
+    /// Increment the weak reference counter
+    fn downgrade(&self);
+
+    /// Increment the weak reference counter on behalf of an existing weak reference. Its
+    /// counterpart is `Algorithm::drop_weak`
+    fn clone_weak(&self);
+
+    /// Increment the strong reference counter unless it has already reached zero and return true
+    /// if successful
+    fn upgrade(&self) -> bool;
+
+    /// Returns the number of weak references (not counting the implicit one held by the strong
+    /// references)
+    fn weak_count(&self) -> usize;
+}
+
+#[cfg(all(feature = "std", not(test)))]
+#[cold]
+#[inline(never)]
 fn abort() {
     std::process::abort()
 }
 
-#[cfg(not(feature = "std"))]
-#[inline]
+#[cfg(any(not(feature = "std"), test))]
+#[cold]
+#[inline(never)]
 fn abort() {
-    // Abort not available on no_std
+    // Abort not available on no_std (and tests need to observe where it fires)
     panic!("Reference count overflow");
 }
+
+#[cold]
+#[inline(never)]
+fn cold() {}
+
+// Stable stand-in for `core::intrinsics::unlikely` - calling a `#[cold]` function in the `true`
+// branch is enough to move it out of the hot path
+#[inline(always)]
+fn unlikely(b: bool) -> bool {
+    if b {
+        cold()
+    }
+    b
+}
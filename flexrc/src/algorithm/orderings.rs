@@ -0,0 +1,40 @@
+use core::sync::atomic::Ordering;
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for super::ArcOrderings {}
+    impl Sealed for super::SeqCstOrderings {}
+}
+
+/// The memory orderings used for the strong count of `SharedMeta`. This trait is sealed and only
+/// sets that are at least as strong as std `Arc` are offered: anything weaker (such as a `Relaxed`
+/// decrement) would let the data be dropped while another thread can still observe writes to it
+pub trait Orderings: private::Sealed + 'static {
+    /// Used to increment the count when cloning
+    const CLONE: Ordering;
+
+    /// Used to decrement the count when dropping
+    const DROP: Ordering;
+
+    /// Used by the fence after the last strong reference drops and by uniqueness checks
+    const ACQUIRE: Ordering;
+}
+
+/// The same orderings as std `Arc` (the default)
+pub struct ArcOrderings;
+
+impl Orderings for ArcOrderings {
+    const CLONE: Ordering = Ordering::Relaxed;
+    const DROP: Ordering = Ordering::Release;
+    const ACQUIRE: Ordering = Ordering::Acquire;
+}
+
+/// `SeqCst` for every strong count operation, for formal models that assume a single total order
+pub struct SeqCstOrderings;
+
+impl Orderings for SeqCstOrderings {
+    const CLONE: Ordering = Ordering::SeqCst;
+    const DROP: Ordering = Ordering::SeqCst;
+    const ACQUIRE: Ordering = Ordering::SeqCst;
+}
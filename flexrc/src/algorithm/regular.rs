@@ -1,54 +1,193 @@
+use core::any::Any;
+#[cfg(not(loom))]
 use core::cell::Cell;
-use core::sync::atomic;
-use core::sync::atomic::{AtomicUsize, Ordering};
+use core::marker::PhantomData;
+use core::mem;
+#[cfg(not(loom))]
+use core::sync::atomic::{self, AtomicUsize, Ordering};
 
-use static_assertions::{assert_eq_align, assert_eq_size, assert_impl_all, assert_not_impl_any};
+#[cfg(loom)]
+use loom::sync::atomic::{self, AtomicUsize, Ordering};
 
-use crate::algorithm::abort;
-use crate::{Algorithm, FlexRc, FlexRcInner};
+use static_assertions::{
+    assert_eq_align, assert_eq_size, assert_impl_all, assert_not_impl_any, const_assert_eq,
+};
 
+use crate::algorithm::{abort, unlikely, ArcOrderings, Orderings, SeqCstOrderings};
+use crate::{Algorithm, ConvertError, FlexRc, FlexRcInner};
+#[cfg(feature = "weak")]
+use crate::{FlexWeak, WeakAlgorithm};
+
+// Weak support costs an extra counter per allocation, so it is only paid for when enabled
+#[cfg(all(not(feature = "weak"), not(loom)))]
+assert_eq_size!(LocalMeta, usize);
+#[cfg(all(feature = "weak", not(loom)))]
+assert_eq_size!(LocalMeta, [usize; 2]);
 assert_eq_size!(LocalMeta, SharedMeta);
 assert_eq_align!(LocalMeta, SharedMeta);
 assert_eq_size!(LocalInner<usize>, SharedInner<usize>);
 assert_eq_align!(LocalInner<usize>, SharedInner<usize>);
 assert_eq_size!(LocalRc<usize>, SharedRc<usize>);
 assert_eq_align!(LocalRc<usize>, SharedRc<usize>);
+// `try_into_other` casts between the inner types for any `T` (including unsized), so the header
+// and the offset of the data must match as well, for every orderings choice
+assert_eq_size!(LocalInner<()>, SharedInner<()>);
+assert_eq_align!(LocalInner<()>, SharedInner<()>);
+const_assert_eq!(
+    mem::offset_of!(LocalInner<u8>, data),
+    mem::offset_of!(SharedInner<u8>, data)
+);
+assert_eq_size!(LocalMeta, SharedMeta<SeqCstOrderings>);
+assert_eq_align!(LocalMeta, SharedMeta<SeqCstOrderings>);
+assert_eq_size!(LocalInner<(), SeqCstOrderings>, SharedInner<(), SeqCstOrderings>);
+assert_eq_align!(LocalInner<(), SeqCstOrderings>, SharedInner<(), SeqCstOrderings>);
+const_assert_eq!(
+    mem::offset_of!(LocalInner<u8, SeqCstOrderings>, data),
+    mem::offset_of!(SharedInner<u8, SeqCstOrderings>, data)
+);
 
 assert_impl_all!(SharedRc<usize>: Send, Sync);
+assert_impl_all!(SharedRc<[u8]>: Send, Sync);
 assert_not_impl_any!(LocalRc<usize>: Send, Sync);
+// Shared handles give out `&T` on every thread holding one and drop `T` on whichever thread is last,
+// so `T` must be both `Send` and `Sync`
+assert_not_impl_any!(SharedRc<core::cell::Cell<u8>>: Send, Sync);
+assert_not_impl_any!(SharedRc<alloc::rc::Rc<u8>>: Send, Sync);
+#[cfg(feature = "std")]
+assert_impl_all!(SharedRc<std::sync::Mutex<u8>>: Send, Sync);
+#[cfg(feature = "std")]
+assert_not_impl_any!(SharedRc<std::sync::MutexGuard<'static, u8>>: Send);
+#[cfg(feature = "weak")]
+assert_impl_all!(SharedWeak<usize>: Send, Sync);
+#[cfg(feature = "weak")]
+assert_not_impl_any!(LocalWeak<usize>: Send, Sync);
+assert_eq_size!(SharedMeta, SharedMeta<SeqCstOrderings>);
+assert_impl_all!(SeqCstSharedRc<usize>: Send, Sync);
+assert_not_impl_any!(SeqCstLocalRc<usize>: Send, Sync);
+
+#[cfg(any(feature = "weak", not(feature = "local_unchecked")))]
+const MAX_LOCAL_COUNT: usize = LocalMeta::MAX_COUNT;
+// Allow some room for overflow: the increment happens before the check, so racing clones can push
+// the count past this value before any of them aborts. Half of the range is far more headroom than
+// there can be threads, so the count never wraps (clones up to and including the one that brings
+// the count to `MAX_SHARED_COUNT + 1` succeed, the next one aborts)
+pub(crate) const MAX_SHARED_COUNT: usize = usize::MAX >> 1;
+// Weak count value used to briefly lock out `downgrade` while checking uniqueness
+#[cfg(feature = "weak")]
+const WEAK_LOCKED: usize = usize::MAX;
+
+// Under loom the shared counters are loom atomics (which are larger than a `usize`), so the local
+// counters must be as well to keep both metas layout identical for conversion
+#[cfg(loom)]
+struct Cell<T>(AtomicUsize, PhantomData<core::cell::Cell<T>>);
 
-const MAX_LOCAL_COUNT: usize = usize::MAX;
-// Allow some room for overflow
-const MAX_SHARED_COUNT: usize = usize::MAX >> 1;
+#[cfg(loom)]
+impl Cell<usize> {
+    fn new(value: usize) -> Self {
+        Self(AtomicUsize::new(value), PhantomData)
+    }
+
+    fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn set(&self, value: usize) {
+        self.0.store(value, Ordering::Relaxed)
+    }
+}
 
 #[repr(C)]
 pub struct LocalMeta {
     count: Cell<usize>,
+    // MUST be at the same position as in `SharedMeta` so weak counts survive conversion
+    #[cfg(feature = "weak")]
+    weak: Cell<usize>,
+}
+
+impl LocalMeta {
+    /// The highest strong count local handles can reach. The clone that would go past it aborts
+    /// instead (unless the check is removed by the `local_unchecked` feature)
+    pub const MAX_COUNT: usize = usize::MAX;
+
+    /// Raw strong count, for asserting intermediate counts in tests
+    #[cfg(any(test, loom))]
+    #[cfg_attr(loom, allow(dead_code))]
+    pub(crate) fn raw_count(&self) -> usize {
+        self.count.get()
+    }
+
+    /// Overwrites the raw strong count, so tests can reach overflow boundaries without cloning
+    /// billions of times
+    #[cfg(all(test, feature = "std", not(loom), not(feature = "local_unchecked")))]
+    pub(crate) fn set_raw_count(&self, count: usize) {
+        self.count.set(count)
+    }
 }
 
 pub type LocalRc<T> = FlexRc<LocalMeta, SharedMeta, T>;
 
-type LocalInner<T> = FlexRcInner<LocalMeta, SharedMeta, T>;
-type SharedInner<T> = FlexRcInner<SharedMeta, LocalMeta, T>;
+#[cfg(all(test, feature = "std", not(loom), not(feature = "local_unchecked")))]
+impl<T> LocalRc<T> {
+    /// Creates a handle whose count claims `count` references, so tests can start right at the
+    /// overflow boundary. The real count must be restored with `set_raw_count` before the last drop
+    pub(crate) fn new_with_raw_count(data: T, count: usize) -> Self {
+        let rc = Self::new(data);
+        rc.as_inner().metadata.set_raw_count(count);
+        rc
+    }
+}
+#[cfg(feature = "weak")]
+pub type LocalWeak<T> = FlexWeak<LocalMeta, SharedMeta, T>;
+#[cfg(feature = "str_deref")]
+pub type LocalStr = LocalRc<[u8]>;
 
-impl Algorithm<LocalMeta, SharedMeta> for LocalMeta {
+/// A local handle whose shared counterpart uses `SeqCst` orderings
+pub type SeqCstLocalRc<T> = FlexRc<LocalMeta, SharedMeta<SeqCstOrderings>, T>;
+
+type LocalInner<T, O = ArcOrderings> = FlexRcInner<LocalMeta, SharedMeta<O>, T>;
+type SharedInner<T, O = ArcOrderings> = FlexRcInner<SharedMeta<O>, LocalMeta, T>;
+
+impl<O: Orderings> Algorithm<LocalMeta, SharedMeta<O>> for LocalMeta {
     #[inline]
     fn create() -> Self {
         Self {
             count: Cell::new(1),
+            // All strong references collectively hold one weak reference
+            #[cfg(feature = "weak")]
+            weak: Cell::new(1),
         }
     }
 
     #[inline]
+    #[cfg(not(feature = "weak"))]
     fn is_unique(&self) -> bool {
         self.count.get() == 1
     }
 
+    #[inline]
+    #[cfg(feature = "weak")]
+    fn is_unique(&self) -> bool {
+        // Any weak handle could later be upgraded, so it counts against uniqueness as well
+        self.count.get() == 1 && self.weak.get() == 1
+    }
+
+    #[inline]
+    fn strong_count(&self) -> usize {
+        self.count.get()
+    }
+
+    #[inline]
+    fn strong_count_relaxed(&self) -> usize {
+        self.count.get()
+    }
+
     #[inline(always)]
     fn clone(&self) {
         let old = self.count.get();
 
-        // TODO: This check adds 15-16% clone overhead - truly needed?
+        // This check adds 15-16% clone overhead. A pointer sized count can't realistically overflow
+        // without first exhausting the address space, so the `local_unchecked` feature removes it
+        #[cfg(not(feature = "local_unchecked"))]
         if old == MAX_LOCAL_COUNT {
             abort()
         }
@@ -56,26 +195,58 @@ impl Algorithm<LocalMeta, SharedMeta> for LocalMeta {
         self.count.set(old + 1);
     }
 
+    #[inline]
+    fn clone_many(&self, n: usize) {
+        let old = self.count.get();
+
+        #[cfg(not(feature = "local_unchecked"))]
+        if n > MAX_LOCAL_COUNT - old {
+            abort()
+        }
+
+        self.count.set(old + n);
+    }
+
     #[inline(always)]
     fn drop(&self) -> bool {
         self.count.set(self.count.get() - 1);
         self.count.get() == 0
     }
 
+    #[inline]
+    #[cfg(not(feature = "weak"))]
+    fn drop_weak(&self) -> bool {
+        // No weak counter, so the strong references were the only ones
+        true
+    }
+
+    #[inline]
+    #[cfg(feature = "weak")]
+    fn drop_weak(&self) -> bool {
+        self.weak.set(self.weak.get() - 1);
+        self.weak.get() == 0
+    }
+
+    #[inline]
+    fn can_convert(&self) -> bool {
+        Algorithm::<LocalMeta, SharedMeta<O>>::is_unique(self)
+    }
+
     #[inline]
     fn try_into_other<T: ?Sized>(
         &self,
-        inner: *mut LocalInner<T>,
-    ) -> Result<*mut SharedInner<T>, *mut LocalInner<T>> {
-        if self.is_unique() {
+        inner: *mut LocalInner<T, O>,
+    ) -> Result<*mut SharedInner<T, O>, *mut LocalInner<T, O>> {
+        if Algorithm::<LocalMeta, SharedMeta<O>>::is_unique(self) {
             // Safety:
             // a) both types are the same struct and identical other than usage of different META types
             // b) type is `repr(C)` so we know the layout
-            // c) although not required, we will ensure same alignment
-            // d) we will validate at compile time `LocalMeta` and `SharedMeta` are same size
+            // c) although not required, same alignment is asserted at compile time (top of module)
+            // d) same size of the metas and inner headers and same data offset are asserted at
+            //    compile time (top of module)
             // e) Cell<usize> and AtomicUsize are same size and layout
             // f) only the two pre-defined metadata pairs are allowed
-            Ok(inner as *mut SharedInner<T>)
+            Ok(inner as *mut SharedInner<T, O>)
         } else {
             Err(inner)
         }
@@ -84,51 +255,280 @@ impl Algorithm<LocalMeta, SharedMeta> for LocalMeta {
     #[inline]
     fn try_to_other<T: ?Sized>(
         &self,
-        inner: *mut LocalInner<T>,
-    ) -> Result<*mut SharedInner<T>, *mut LocalInner<T>> {
+        inner: *mut LocalInner<T, O>,
+    ) -> Result<*mut SharedInner<T, O>, *mut LocalInner<T, O>> {
         // This is never safe to do
         Err(inner)
     }
 }
 
+#[cfg(feature = "weak")]
+impl<O: Orderings> WeakAlgorithm<LocalMeta, SharedMeta<O>> for LocalMeta {
+    #[inline]
+    fn downgrade(&self) {
+        let old = self.weak.get();
+
+        if old == MAX_LOCAL_COUNT {
+            abort()
+        }
+        self.weak.set(old + 1);
+    }
+
+    #[inline]
+    fn clone_weak(&self) {
+        // Nothing locks the local weak count, so this is the same as `downgrade`
+        WeakAlgorithm::<LocalMeta, SharedMeta<O>>::downgrade(self)
+    }
+
+    #[inline]
+    fn upgrade(&self) -> bool {
+        let old = self.count.get();
+
+        if old == 0 {
+            false
+        } else {
+            #[cfg(not(feature = "local_unchecked"))]
+            if old == MAX_LOCAL_COUNT {
+                abort()
+            }
+
+            self.count.set(old + 1);
+            true
+        }
+    }
+
+    #[inline]
+    fn weak_count(&self) -> usize {
+        self.weak.get() - 1
+    }
+}
+
+/// Metadata for shared handles. `O` selects the memory orderings of the strong count (see
+/// `Orderings`) and only changes the type, never the layout
 #[repr(C)]
-pub struct SharedMeta {
+pub struct SharedMeta<O: Orderings = ArcOrderings> {
     count: AtomicUsize,
+    // MUST be at the same position as in `LocalMeta` so weak counts survive conversion
+    #[cfg(feature = "weak")]
+    weak: AtomicUsize,
+    phantom: PhantomData<O>,
+}
+
+impl<O: Orderings> SharedMeta<O> {
+    /// The highest strong count shared handles can reach. The clone that would go past it aborts
+    /// instead. Since the check happens after the increment, racing clones may briefly push the
+    /// raw counter further, but none of them survive
+    pub const MAX_COUNT: usize = MAX_SHARED_COUNT + 1;
+
+    /// Raw strong count, for asserting intermediate counts in tests (a `Relaxed` load, so models
+    /// must establish ordering some other way, such as joining threads)
+    #[cfg(any(test, loom))]
+    #[cfg_attr(loom, allow(dead_code))]
+    pub(crate) fn raw_count(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Overwrites the raw strong count, so tests can reach overflow boundaries without cloning
+    /// billions of times
+    #[cfg(all(test, feature = "std", not(loom)))]
+    pub(crate) fn set_raw_count(&self, count: usize) {
+        self.count.store(count, Ordering::Relaxed)
+    }
 }
 
 pub type SharedRc<T> = FlexRc<SharedMeta, LocalMeta, T>;
 
+#[cfg(all(test, feature = "std", not(loom)))]
+impl<T> SharedRc<T> {
+    /// Creates a handle whose count claims `count` references, so tests can start right at the
+    /// overflow boundary. The real count must be restored with `set_raw_count` before the last drop
+    pub(crate) fn new_with_raw_count(data: T, count: usize) -> Self {
+        let rc = Self::new(data);
+        rc.as_inner().metadata.set_raw_count(count);
+        rc
+    }
+}
+#[cfg(feature = "weak")]
+pub type SharedWeak<T> = FlexWeak<SharedMeta, LocalMeta, T>;
+#[cfg(feature = "str_deref")]
+pub type SharedStr = SharedRc<[u8]>;
+
+/// A shared handle that uses `SeqCst` orderings for its strong count
+pub type SeqCstSharedRc<T> = FlexRc<SharedMeta<SeqCstOrderings>, LocalMeta, T>;
+
 // SAFETY: We ensure what we are holding is Sync/Send and we have been careful to ensure invariants
 // that allow these marked to be safe
-unsafe impl<T: Send + Sync> Send for SharedRc<T> {}
-unsafe impl<T: Send + Sync> Sync for SharedRc<T> {}
+unsafe impl<T: ?Sized + Send + Sync, O: Orderings> Send for FlexRc<SharedMeta<O>, LocalMeta, T> {}
+unsafe impl<T: ?Sized + Send + Sync, O: Orderings> Sync for FlexRc<SharedMeta<O>, LocalMeta, T> {}
+#[cfg(feature = "weak")]
+unsafe impl<T: ?Sized + Send + Sync, O: Orderings> Send for FlexWeak<SharedMeta<O>, LocalMeta, T> {}
+#[cfg(feature = "weak")]
+unsafe impl<T: ?Sized + Send + Sync, O: Orderings> Sync for FlexWeak<SharedMeta<O>, LocalMeta, T> {}
+
+impl<T: ?Sized> SharedRc<T> {
+    /// Converts this into a local handle if it is the only reference (strong or weak) to its
+    /// data, else returns itself. The uniqueness check is an `Acquire` load that synchronizes with
+    /// the release of every other handle, so their writes are visible to the returned handle.
+    ///
+    /// The resulting `LocalRc` must not be sent to another thread (the type system already
+    /// prevents this, but it is the reason this only succeeds when unique: no other thread can
+    /// hold a handle to the data)
+    #[inline]
+    pub fn try_into_local_here(self) -> Result<LocalRc<T>, Self> {
+        self.try_into_other()
+    }
+
+    /// Same as `try_into_local_here`, but the error says why the conversion failed
+    #[inline]
+    pub fn try_into_local(self) -> Result<LocalRc<T>, ConvertError<Self>> {
+        self.try_into_other().map_err(ConvertError::NotUnique)
+    }
+}
+
+impl SharedRc<dyn Any + Send + Sync> {
+    /// Downcasts to the concrete type and converts to a local handle in one step. Fails, returning
+    /// the handle unchanged, if the data is not a `T` or this is not the only reference to it
+    #[inline]
+    pub fn downcast_into_local<T: Any + Send + Sync>(self) -> Result<LocalRc<T>, Self> {
+        if !self.as_inner().data.is::<T>() {
+            return Err(self);
+        }
+
+        let local = self.try_into_local_here()?;
+        // SAFETY: We checked the type above, and the conversion kept the same data
+        Ok(unsafe { local.downcast_unchecked() })
+    }
+}
+
+impl<T: ?Sized> LocalRc<T> {
+    /// Converts this into a shared handle if it is the only reference (strong or weak) to its
+    /// data, else returns itself along with the reason
+    #[inline]
+    pub fn try_into_shared(self) -> Result<SharedRc<T>, ConvertError<Self>> {
+        self.try_into_other().map_err(ConvertError::NotUnique)
+    }
 
-impl Algorithm<SharedMeta, LocalMeta> for SharedMeta {
+    /// Ends the "build with `get_mut`, then publish" pattern: converts the (unique) handle in place
+    /// into a `SharedRc` that can be sent to other threads, without copying the data. The same
+    /// conversion as `try_into_shared`, so it fails (returning the handle) while other references
+    /// exist
+    #[inline]
+    pub fn freeze(self) -> Result<SharedRc<T>, Self> {
+        self.try_into_other()
+    }
+}
+
+// Same as `try_into_other`, so conversions can be used through the standard trait bounds
+impl<T: ?Sized> TryFrom<SharedRc<T>> for LocalRc<T> {
+    type Error = SharedRc<T>;
+
+    #[inline]
+    fn try_from(rc: SharedRc<T>) -> Result<Self, Self::Error> {
+        rc.try_into_other()
+    }
+}
+
+impl<T: ?Sized> TryFrom<LocalRc<T>> for SharedRc<T> {
+    type Error = LocalRc<T>;
+
+    #[inline]
+    fn try_from(rc: LocalRc<T>) -> Result<Self, Self::Error> {
+        rc.try_into_other()
+    }
+}
+
+impl<O: Orderings> Algorithm<SharedMeta<O>, LocalMeta> for SharedMeta<O> {
     #[inline]
     fn create() -> Self {
         Self {
             count: AtomicUsize::new(1),
+            // All strong references collectively hold one weak reference
+            #[cfg(feature = "weak")]
+            weak: AtomicUsize::new(1),
+            phantom: PhantomData,
         }
     }
 
     #[inline]
+    #[cfg(not(feature = "weak"))]
     fn is_unique(&self) -> bool {
         // Long discussion on why this ordering is required: https://github.com/servo/servo/issues/21186
-        self.count.load(Ordering::Acquire) == 1
+        self.count.load(O::ACQUIRE) == 1
+    }
+
+    #[inline]
+    #[cfg(feature = "weak")]
+    fn is_unique(&self) -> bool {
+        // Lock out `downgrade` while we check the strong count (same approach as std `Arc`), else a
+        // weak could be created, upgraded and dropped again between the two loads
+        if self
+            .weak
+            .compare_exchange(1, WEAK_LOCKED, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
+            // Long discussion on why this ordering is required: https://github.com/servo/servo/issues/21186
+            let unique = self.count.load(O::ACQUIRE) == 1;
+            self.weak.store(1, Ordering::Release);
+            unique
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn strong_count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    #[inline]
+    fn strong_count_relaxed(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
     }
 
     #[inline(always)]
     fn clone(&self) {
-        let old = self.count.fetch_add(1, Ordering::Relaxed);
+        let old = self.count.fetch_add(1, O::CLONE);
+
+        if unlikely(old > MAX_SHARED_COUNT) {
+            abort()
+        }
+    }
+
+    #[inline]
+    fn clone_many(&self, n: usize) {
+        // Checked up front so the increment itself can't wrap
+        if unlikely(n > MAX_SHARED_COUNT) {
+            abort()
+        }
+        let old = self.count.fetch_add(n, O::CLONE);
 
-        if old > MAX_SHARED_COUNT {
+        // Same bound as `clone`, applied to the last of the `n` new references
+        if unlikely(old + n > MAX_SHARED_COUNT + 1) {
             abort()
         }
     }
 
     #[inline(always)]
     fn drop(&self) -> bool {
-        if self.count.fetch_sub(1, Ordering::Release) == 1 {
+        if self.count.fetch_sub(1, O::DROP) == 1 {
+            atomic::fence(O::ACQUIRE);
+            true
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    #[cfg(not(feature = "weak"))]
+    fn drop_weak(&self) -> bool {
+        // No weak counter, so the strong references were the only ones
+        true
+    }
+
+    #[inline]
+    #[cfg(feature = "weak")]
+    fn drop_weak(&self) -> bool {
+        if self.weak.fetch_sub(1, Ordering::Release) == 1 {
             atomic::fence(Ordering::Acquire);
             true
         } else {
@@ -136,20 +536,36 @@ impl Algorithm<SharedMeta, LocalMeta> for SharedMeta {
         }
     }
 
+    #[inline]
+    fn can_convert(&self) -> bool {
+        // Unlike `is_unique`, never lock the weak count so this stays a pure read
+        #[cfg(feature = "weak")]
+        if self.weak.load(Ordering::Acquire) != 1 {
+            return false;
+        }
+
+        self.count.load(O::ACQUIRE) == 1
+    }
+
     #[inline]
     fn try_into_other<T: ?Sized>(
         &self,
-        inner: *mut SharedInner<T>,
-    ) -> Result<*mut LocalInner<T>, *mut SharedInner<T>> {
+        inner: *mut SharedInner<T, O>,
+    ) -> Result<*mut LocalInner<T, O>, *mut SharedInner<T, O>> {
+        // Nothing can race us between the check and the cast: a new strong handle can only come
+        // from cloning an existing one and the caller gives up the only one by converting it. With
+        // weak support, `is_unique` also requires there be no weak handles (with `downgrade` locked
+        // out during the check), so no upgrade can sneak in either
         if self.is_unique() {
             // Safety:
             // a) both types are the same struct and identical other than usage of different META types
             // b) type is `repr(C)` so we know the layout
-            // c) although not required, we will ensure same alignment (TODO)
-            // d) we will validate at compile time `LocalMeta` and `SharedMeta` are same size (TODO)
+            // c) although not required, same alignment is asserted at compile time (top of module)
+            // d) same size of the metas and inner headers and same data offset are asserted at
+            //    compile time (top of module)
             // e) Cell<usize> and AtomicUsize are same size and layout
             // f) only the two pre-defined metadata pairs are allowed
-            Ok(inner as *mut LocalInner<T>)
+            Ok(inner as *mut LocalInner<T, O>)
         } else {
             Err(inner)
         }
@@ -158,9 +574,89 @@ impl Algorithm<SharedMeta, LocalMeta> for SharedMeta {
     #[inline]
     fn try_to_other<T: ?Sized>(
         &self,
-        inner: *mut SharedInner<T>,
-    ) -> Result<*mut LocalInner<T>, *mut SharedInner<T>> {
+        inner: *mut SharedInner<T, O>,
+    ) -> Result<*mut LocalInner<T, O>, *mut SharedInner<T, O>> {
         // This is never safe to do
         Err(inner)
     }
 }
+
+#[cfg(feature = "weak")]
+impl<O: Orderings> WeakAlgorithm<SharedMeta<O>, LocalMeta> for SharedMeta<O> {
+    #[inline]
+    fn downgrade(&self) {
+        let mut old = self.weak.load(Ordering::Relaxed);
+
+        loop {
+            // Spin while `is_unique` has the weak count locked
+            if old == WEAK_LOCKED {
+                #[cfg(not(loom))]
+                core::hint::spin_loop();
+                #[cfg(loom)]
+                loom::thread::yield_now();
+                old = self.weak.load(Ordering::Relaxed);
+                continue;
+            }
+
+            if unlikely(old > MAX_SHARED_COUNT) {
+                abort()
+            }
+
+            match self.weak.compare_exchange_weak(
+                old,
+                old + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(actual) => old = actual,
+            }
+        }
+    }
+
+    #[inline]
+    fn clone_weak(&self) {
+        // We hold a weak reference, so the weak count is above one and `is_unique` can't lock it.
+        // Like cloning a strong reference, the new reference needs no synchronization
+        let old = self.weak.fetch_add(1, Ordering::Relaxed);
+
+        if unlikely(old > MAX_SHARED_COUNT) {
+            abort()
+        }
+    }
+
+    #[inline]
+    fn upgrade(&self) -> bool {
+        let mut old = self.count.load(Ordering::Relaxed);
+
+        loop {
+            // Once the strong count reaches zero the data is gone and can't be revived
+            if old == 0 {
+                return false;
+            }
+
+            if unlikely(old > MAX_SHARED_COUNT) {
+                abort()
+            }
+
+            match self.count.compare_exchange_weak(
+                old,
+                old + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return true,
+                Err(actual) => old = actual,
+            }
+        }
+    }
+
+    #[inline]
+    fn weak_count(&self) -> usize {
+        match self.weak.load(Ordering::Relaxed) {
+            // Locked by `is_unique` which only happens when there are no weak references
+            WEAK_LOCKED => 0,
+            weak => weak - 1,
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use core::mem;
+use core::ops::Deref;
+use core::ptr;
+
+use crate::{Algorithm, FlexRc, LocalMeta, SharedMeta};
+
+// Smallest capacity allocated once something is written
+const MIN_CAPACITY: usize = 8;
+
+// *** FlexBuf ***
+
+/// An append-only byte buffer that is frozen into a `FlexRc<[u8]>`. Unlike `StrBuilder`, the bytes
+/// are written straight into an allocation that already has room for the reference count header,
+/// so `freeze` only has to trim any spare capacity instead of copying. Like `from_slice`, this is
+/// not available with the `str_deref` feature since the bytes may not be valid UTF-8
+pub struct FlexBuf<META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    // Unique handle whose slice length is our capacity. Only the first `len` bytes are initialized
+    buf: FlexRc<META, META2, [mem::MaybeUninit<u8>]>,
+    len: usize,
+}
+
+/// Builds a `LocalRc<[u8]>`
+pub type LocalBuf = FlexBuf<LocalMeta, SharedMeta>;
+
+/// Builds a `SharedRc<[u8]>`
+pub type SharedBuf = FlexBuf<SharedMeta, LocalMeta>;
+
+impl<META, META2> FlexBuf<META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Preallocates room for `capacity` bytes, so writes up to that size never reallocate
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: FlexRc::<META, META2, [u8]>::new_slice_uninit(capacity),
+            len: 0,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Ensures there is room for at least `additional` more bytes. The allocation (header
+    /// included) is moved with `realloc`, so the written bytes are kept without copying them
+    /// ourselves
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self.len.checked_add(additional).expect("capacity overflow");
+
+        if needed > self.capacity() {
+            let capacity = needed
+                .max(self.capacity().saturating_mul(2))
+                .max(MIN_CAPACITY);
+            // SAFETY: We never hand out handles to the buffer, so we are the unique owner
+            unsafe { self.buf.resize(capacity) };
+        }
+    }
+
+    #[inline]
+    pub fn push(&mut self, byte: u8) {
+        self.extend_from_slice(&[byte]);
+    }
+
+    #[inline]
+    pub fn extend_from_slice(&mut self, bytes: &[u8]) {
+        self.reserve(bytes.len());
+
+        // SAFETY: We are the unique owner and just made sure there is room for `bytes` after
+        // `len`. `MaybeUninit<u8>` is layout identical to `u8`
+        unsafe {
+            let data = self.buf.data_mut();
+            ptr::copy_nonoverlapping(
+                bytes.as_ptr(),
+                data.as_mut_ptr().add(self.len) as *mut u8,
+                bytes.len(),
+            );
+        }
+        self.len += bytes.len();
+    }
+
+    /// Converts the buffer into a handle in place, releasing any spare capacity
+    #[inline]
+    pub fn freeze(self) -> FlexRc<META, META2, [u8]> {
+        // SAFETY: We are the unique owner and the first `len` bytes were written
+        unsafe { self.buf.set_len_and_assume_init(self.len) }
+    }
+}
+
+impl<META, META2> Default for FlexBuf<META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<META, META2> Deref for FlexBuf<META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: The first `len` bytes were written, and `MaybeUninit<u8>` is layout identical
+        // to `u8`
+        unsafe { &*(&self.buf[..self.len] as *const [mem::MaybeUninit<u8>] as *const [u8]) }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<META, META2> std::io::Write for FlexBuf<META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use alloc::string::String;
+use core::fmt;
+use core::marker::PhantomData;
+
+use crate::{Algorithm, FlexRc, LocalMeta, SharedMeta};
+
+// *** StrBuilder ***
+
+/// Builds a string incrementally in a growable buffer, then copies it once into an exact-length
+/// `FlexRc<str>`
+pub struct StrBuilder<META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    buf: String,
+    phantom: PhantomData<(META, META2)>,
+}
+
+/// Builds a `LocalRc<str>`
+pub type LocalStrBuilder = StrBuilder<LocalMeta, SharedMeta>;
+
+/// Builds a `SharedRc<str>`
+pub type SharedStrBuilder = StrBuilder<SharedMeta, LocalMeta>;
+
+impl<META, META2> StrBuilder<META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Preallocates room for `capacity` bytes, so pushes up to that size never reallocate
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: String::with_capacity(capacity),
+            phantom: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn push(&mut self, ch: char) {
+        self.buf.push(ch);
+    }
+
+    #[inline]
+    pub fn push_str(&mut self, s: &str) {
+        self.buf.push_str(s);
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Copies the built string into a new exact-length allocation
+    #[inline]
+    pub fn finish(self) -> FlexRc<META, META2, str> {
+        FlexRc::from(self.buf.as_str())
+    }
+}
+
+impl<META, META2> Default for StrBuilder<META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<META, META2> fmt::Write for StrBuilder<META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
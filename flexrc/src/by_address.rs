@@ -0,0 +1,110 @@
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+
+use crate::{Algorithm, FlexRc};
+
+// *** ByAddress ***
+
+/// Wraps a handle so that equality, hashing and ordering are based on the address of its data
+/// instead of its value. The ordering is total and consistent with equality, so handles can be
+/// sorted into a stable order (for example, to acquire locks in address order to avoid deadlock)
+pub struct ByAddress<META, META2, T>(pub FlexRc<META, META2, T>)
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized;
+
+impl<META, META2, T> ByAddress<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    #[inline(always)]
+    fn addr(&self) -> usize {
+        self.0.identity()
+    }
+}
+
+impl<META, META2, T> Clone for ByAddress<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<META, META2, T> Deref for ByAddress<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    type Target = FlexRc<META, META2, T>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<META, META2, T> PartialEq for ByAddress<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.addr() == other.addr()
+    }
+}
+
+impl<META, META2, T> Eq for ByAddress<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+}
+
+impl<META, META2, T> PartialOrd for ByAddress<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<META, META2, T> Ord for ByAddress<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.addr().cmp(&other.addr())
+    }
+}
+
+impl<META, META2, T> Hash for ByAddress<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.addr().hash(state)
+    }
+}
@@ -0,0 +1,234 @@
+use core::ops::Deref;
+
+use static_assertions::{assert_impl_all, assert_not_impl_any};
+
+use crate::{Algorithm, FlexRc, LocalMeta, SharedMeta};
+
+assert_impl_all!(SharedBytes: Send, Sync);
+assert_not_impl_any!(LocalBytes: Send, Sync);
+
+// *** FlexBytes ***
+
+/// A view into a range of a byte slice handle. Views share the underlying allocation, so
+/// splitting never copies the bytes
+pub struct FlexBytes<META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    rc: FlexRc<META, META2, [u8]>,
+    start: usize,
+    end: usize,
+}
+
+/// A local view into a range of bytes
+pub type LocalBytes = FlexBytes<LocalMeta, SharedMeta>;
+
+/// A shared view into a range of bytes
+pub type SharedBytes = FlexBytes<SharedMeta, LocalMeta>;
+
+impl<META, META2> FlexBytes<META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.rc.as_bytes()[self.start..self.end]
+    }
+
+    /// Splits the view in two at `at`, leaving `[0, at)` in `self` and returning `[at, len)`. Both
+    /// halves share the same allocation. Panics if `at > len`
+    #[inline]
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(
+            at <= self.len(),
+            "split_off out of bounds: {} > {}",
+            at,
+            self.len()
+        );
+
+        let mid = self.start + at;
+        let other = Self {
+            rc: self.rc.clone(),
+            start: mid,
+            end: self.end,
+        };
+        self.end = mid;
+        other
+    }
+
+    /// Returns an iterator over non-overlapping views of `size` bytes each (the last one may be
+    /// shorter), like `slice::chunks`. Each view shares the allocation and keeps it alive. Panics if
+    /// `size` is zero
+    #[inline]
+    pub fn chunks(&self, size: usize) -> Chunks<'_, META, META2> {
+        assert!(size != 0, "chunk size must be non-zero");
+        Chunks {
+            bytes: self,
+            pos: self.start,
+            size,
+        }
+    }
+
+    /// Returns an iterator over all overlapping views of `size` bytes each, like `slice::windows`.
+    /// Each view shares the allocation and keeps it alive. Panics if `size` is zero
+    #[inline]
+    pub fn windows(&self, size: usize) -> Windows<'_, META, META2> {
+        assert!(size != 0, "window size must be non-zero");
+        Windows {
+            bytes: self,
+            pos: self.start,
+            size,
+        }
+    }
+
+    #[inline]
+    fn view(&self, start: usize, end: usize) -> Self {
+        Self {
+            rc: self.rc.clone(),
+            start,
+            end,
+        }
+    }
+}
+
+impl<META, META2> From<FlexRc<META, META2, [u8]>> for FlexBytes<META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn from(rc: FlexRc<META, META2, [u8]>) -> Self {
+        let end = rc.as_bytes().len();
+        Self { rc, start: 0, end }
+    }
+}
+
+impl<META, META2> Clone for FlexBytes<META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            rc: self.rc.clone(),
+            start: self.start,
+            end: self.end,
+        }
+    }
+}
+
+impl<META, META2> Deref for FlexBytes<META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.as_bytes()
+    }
+}
+
+// *** Chunks ***
+
+/// Iterator returned by `FlexBytes::chunks`
+pub struct Chunks<'a, META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    bytes: &'a FlexBytes<META, META2>,
+    pos: usize,
+    size: usize,
+}
+
+impl<'a, META, META2> Iterator for Chunks<'a, META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    type Item = FlexBytes<META, META2>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.bytes.end {
+            return None;
+        }
+
+        let start = self.pos;
+        self.pos += usize::min(self.size, self.bytes.end - start);
+        Some(self.bytes.view(start, self.pos))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.bytes.end - self.pos).div_ceil(self.size);
+        (len, Some(len))
+    }
+}
+
+impl<'a, META, META2> ExactSizeIterator for Chunks<'a, META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+}
+
+// *** Windows ***
+
+/// Iterator returned by `FlexBytes::windows`
+pub struct Windows<'a, META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    bytes: &'a FlexBytes<META, META2>,
+    pos: usize,
+    size: usize,
+}
+
+impl<'a, META, META2> Iterator for Windows<'a, META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    type Item = FlexBytes<META, META2>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bytes.end - self.pos < self.size {
+            return None;
+        }
+
+        let start = self.pos;
+        self.pos += 1;
+        Some(self.bytes.view(start, start + self.size))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.bytes.end - self.pos + 1).saturating_sub(self.size);
+        (len, Some(len))
+    }
+}
+
+impl<'a, META, META2> ExactSizeIterator for Windows<'a, META, META2>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+}
@@ -0,0 +1,46 @@
+use core::cell::{Ref, RefCell, RefMut};
+#[cfg(feature = "std")]
+use std::sync::{LockResult, Mutex, MutexGuard, TryLockResult};
+
+use crate::LocalRc;
+#[cfg(feature = "std")]
+use crate::SharedRc;
+
+// Handles only hand out `&T`, so shared mutation requires interior mutability. These aliases cover
+// the most common patterns and forward to the inner cell/mutex so no extra deref is needed
+
+/// A local handle to a `RefCell` for single threaded shared mutation
+pub type LocalCell<T> = LocalRc<RefCell<T>>;
+
+/// A shared handle to a `Mutex` for multithreaded shared mutation
+#[cfg(feature = "std")]
+pub type SharedMutex<T> = SharedRc<Mutex<T>>;
+
+impl<T> LocalCell<T> {
+    /// Immutably borrows the inner value. Panics if it is currently mutably borrowed
+    #[inline]
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.as_inner().data.borrow()
+    }
+
+    /// Mutably borrows the inner value. Panics if it is currently borrowed
+    #[inline]
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        self.as_inner().data.borrow_mut()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> SharedMutex<T> {
+    /// Acquires the inner mutex, blocking until it is available
+    #[inline]
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        self.as_inner().data.lock()
+    }
+
+    /// Attempts to acquire the inner mutex without blocking
+    #[inline]
+    pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
+        self.as_inner().data.try_lock()
+    }
+}
@@ -0,0 +1,37 @@
+use crate::{LocalHybridRc, LocalRc, SharedHybridRc, SharedRc};
+
+// The regular and hybrid families use different metadata layouts, so moving an instance from one
+// family to the other can't be a cast like `try_into_other`. These allocate a new instance of the
+// target family holding a clone of the data, leaving the original (and its other handles) as is
+
+impl<T: Clone> LocalRc<T> {
+    /// Allocates a new hybrid handle holding a clone of the data. This is NOT zero cost
+    #[inline]
+    pub fn to_hybrid(&self) -> LocalHybridRc<T> {
+        LocalHybridRc::new(self.as_inner().data.clone())
+    }
+}
+
+impl<T: Clone> SharedRc<T> {
+    /// Allocates a new hybrid handle holding a clone of the data. This is NOT zero cost
+    #[inline]
+    pub fn to_hybrid(&self) -> SharedHybridRc<T> {
+        SharedHybridRc::new(self.as_inner().data.clone())
+    }
+}
+
+impl<T: Clone> LocalHybridRc<T> {
+    /// Allocates a new regular handle holding a clone of the data. This is NOT zero cost
+    #[inline]
+    pub fn to_regular(&self) -> LocalRc<T> {
+        LocalRc::new(self.as_inner().data.clone())
+    }
+}
+
+impl<T: Clone> SharedHybridRc<T> {
+    /// Allocates a new regular handle holding a clone of the data. This is NOT zero cost
+    #[inline]
+    pub fn to_regular(&self) -> SharedRc<T> {
+        SharedRc::new(self.as_inner().data.clone())
+    }
+}
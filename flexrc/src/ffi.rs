@@ -0,0 +1,91 @@
+//! C compatible handles to shared byte buffers. The exported symbols, as a C header:
+//!
+//! ```c
+//! typedef struct {
+//!     const void *ptr; /* opaque, never dereference */
+//!     size_t len;
+//! } FlexRcFfi;
+//!
+//! /* Returns a new handle to the same buffer, incrementing the reference count */
+//! FlexRcFfi flexrc_clone(FlexRcFfi handle);
+//! /* Releases the handle. It must not be used afterwards */
+//! void flexrc_drop(FlexRcFfi handle);
+//! /* Returns a pointer to the first byte, valid while any handle to the buffer is alive */
+//! const uint8_t *flexrc_data_ptr(FlexRcFfi handle);
+//! /* Returns the number of bytes in the buffer */
+//! size_t flexrc_len(FlexRcFfi handle);
+//! ```
+
+use core::ffi::c_void;
+use core::mem;
+use core::ptr::{self, NonNull};
+
+use crate::{FlexRcInner, LocalMeta, SharedMeta, SharedRc};
+
+// *** FlexRcFfi ***
+
+/// A `SharedRc<[u8]>` in a form that can cross an FFI boundary. Each value owns one strong
+/// reference, which is released by `flexrc_drop` or by turning it back into a `SharedRc`
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct FlexRcFfi {
+    ptr: *const c_void,
+    len: usize,
+}
+
+impl SharedRc<[u8]> {
+    /// Converts the handle into an FFI handle without changing the reference count
+    #[inline]
+    pub fn into_ffi(self) -> FlexRcFfi {
+        let len = self.as_bytes().len();
+        let this = mem::ManuallyDrop::new(self);
+
+        FlexRcFfi {
+            ptr: this.ptr.as_ptr() as *const c_void,
+            len,
+        }
+    }
+
+    /// Converts an FFI handle back into a handle without changing the reference count
+    ///
+    /// # Safety
+    /// `handle` must have come from `into_ffi` or `flexrc_clone` and must not be used again
+    #[inline]
+    pub unsafe fn from_ffi(handle: FlexRcFfi) -> Self {
+        // This just makes a "fat pointer" setting the correct # of bytes in the metadata
+        let inner = ptr::slice_from_raw_parts_mut(handle.ptr as *mut u8, handle.len)
+            as *mut FlexRcInner<SharedMeta, LocalMeta, [u8]>;
+        Self::from_inner(NonNull::new_unchecked(inner))
+    }
+}
+
+/// # Safety
+/// `handle` must be a live handle
+#[no_mangle]
+pub unsafe extern "C" fn flexrc_clone(handle: FlexRcFfi) -> FlexRcFfi {
+    // Avoid drop to ensure no ref count decrement
+    let rc = mem::ManuallyDrop::new(SharedRc::from_ffi(handle));
+    SharedRc::clone(&rc).into_ffi()
+}
+
+/// # Safety
+/// `handle` must be a live handle, and neither it nor any copy of it may be used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn flexrc_drop(handle: FlexRcFfi) {
+    drop(SharedRc::from_ffi(handle));
+}
+
+/// # Safety
+/// `handle` must be a live handle
+#[no_mangle]
+pub unsafe extern "C" fn flexrc_data_ptr(handle: FlexRcFfi) -> *const u8 {
+    let rc = mem::ManuallyDrop::new(SharedRc::from_ffi(handle));
+    rc.as_bytes().as_ptr()
+}
+
+/// # Safety
+/// `handle` must be a live handle
+#[no_mangle]
+pub unsafe extern "C" fn flexrc_len(handle: FlexRcFfi) -> usize {
+    handle.len
+}
@@ -0,0 +1,176 @@
+use std::borrow::Borrow;
+#[cfg(feature = "weak")]
+use std::collections::hash_map::RandomState;
+#[cfg(feature = "weak")]
+use std::collections::HashMap;
+#[cfg(not(feature = "weak"))]
+use std::collections::HashSet;
+#[cfg(feature = "weak")]
+use std::hash::BuildHasher;
+use std::hash::Hash;
+use std::sync::Mutex;
+
+use crate::SharedRc;
+#[cfg(feature = "weak")]
+use crate::SharedWeak;
+
+/// Hands out `SharedRc` handles while guaranteeing that identical content always shares a single
+/// allocation.
+///
+/// With the `weak` feature the interner only keeps weak handles, so a value is dropped as soon as
+/// the last handle given out for it is, and its (now dead) entry is removed the next time an equal
+/// value is interned or on `purge`. Without it the interner holds a strong handle to every value,
+/// which is only released by `purge`.
+pub struct FlexInterner<T: ?Sized> {
+    // Weak handles can't be looked up by content, so entries are grouped by the hash of their
+    // value instead (almost always one per group)
+    #[cfg(feature = "weak")]
+    entries: Mutex<HashMap<u64, Vec<SharedWeak<T>>>>,
+    #[cfg(feature = "weak")]
+    hasher: RandomState,
+    #[cfg(not(feature = "weak"))]
+    entries: Mutex<HashSet<SharedRc<T>>>,
+}
+
+impl<T: ?Sized> Default for FlexInterner<T> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            entries: Mutex::default(),
+            #[cfg(feature = "weak")]
+            hasher: RandomState::new(),
+        }
+    }
+}
+
+impl<T> FlexInterner<T>
+where
+    T: Hash + Eq + ?Sized,
+{
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(feature = "weak")]
+impl<T> FlexInterner<T>
+where
+    T: Hash + Eq + ?Sized,
+{
+    /// Returns the number of entries held by the interner, including those whose value has been
+    /// dropped but that haven't been removed yet
+    #[inline]
+    pub fn len(&self) -> usize {
+        let entries = self.entries.lock().expect("poisoned lock");
+        entries.values().map(Vec::len).sum()
+    }
+
+    /// Removes every entry whose value has been dropped and returns how many were removed
+    pub fn purge(&self) -> usize {
+        let mut entries = self.entries.lock().expect("poisoned lock");
+        let mut removed = 0;
+
+        entries.retain(|_, group| {
+            let old_len = group.len();
+            group.retain(|weak| weak.strong_count() != 0);
+            removed += old_len - group.len();
+            !group.is_empty()
+        });
+        removed
+    }
+
+    fn intern_with<V>(&self, value: V, create: impl FnOnce(V) -> SharedRc<T>) -> SharedRc<T>
+    where
+        V: Borrow<T>,
+    {
+        let hash = self.hasher.hash_one(value.borrow());
+        let mut entries = self.entries.lock().expect("poisoned lock");
+        let group = entries.entry(hash).or_default();
+
+        // Look for an equal value, removing any dead entries along the way
+        let mut found = None;
+        group.retain(|weak| match weak.upgrade() {
+            Some(rc) => {
+                if found.is_none() && rc.as_inner().data == *value.borrow() {
+                    found = Some(rc);
+                }
+                true
+            }
+            None => false,
+        });
+
+        found.unwrap_or_else(|| {
+            let rc = create(value);
+            group.push(rc.downgrade());
+            rc
+        })
+    }
+}
+
+#[cfg(not(feature = "weak"))]
+impl<T> FlexInterner<T>
+where
+    T: Hash + Eq + ?Sized,
+{
+    /// Returns the number of values currently held by the interner
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.lock().expect("poisoned lock").len()
+    }
+
+    /// Removes every value no longer referenced outside of the interner and returns how many were
+    /// removed
+    pub fn purge(&self) -> usize {
+        let mut entries = self.entries.lock().expect("poisoned lock");
+        let old_len = entries.len();
+
+        // A count of 1 means the interner holds the only handle and since new handles can only be
+        // handed out while holding the lock, nobody can be racing us to clone it
+        entries.retain(|rc| rc.strong_count() > 1);
+        old_len - entries.len()
+    }
+
+    fn intern_with<V>(&self, value: V, create: impl FnOnce(V) -> SharedRc<T>) -> SharedRc<T>
+    where
+        V: Borrow<T>,
+    {
+        let mut entries = self.entries.lock().expect("poisoned lock");
+
+        match entries.get(value.borrow()) {
+            Some(rc) => rc.clone(),
+            None => {
+                let rc = create(value);
+                entries.insert(rc.clone());
+                rc
+            }
+        }
+    }
+}
+
+impl<T> FlexInterner<T>
+where
+    T: Hash + Eq,
+{
+    /// Returns a handle to the value equal to `value`, allocating a new one only if no such value
+    /// has been interned yet
+    #[inline]
+    pub fn intern(&self, value: T) -> SharedRc<T> {
+        self.intern_with(value, SharedRc::new)
+    }
+}
+
+impl FlexInterner<[u8]> {
+    /// Returns a handle to the string equal to `s`, allocating a new one only if no such string
+    /// has been interned yet
+    #[inline]
+    pub fn intern(&self, s: impl AsRef<str>) -> SharedRc<[u8]> {
+        let s = s.as_ref();
+        self.intern_with(s.as_bytes(), |_| SharedRc::from_str_ref(s))
+    }
+}
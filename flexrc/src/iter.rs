@@ -0,0 +1,210 @@
+use core::iter::FusedIterator;
+use core::mem::ManuallyDrop;
+use core::ops::Range;
+use core::{fmt, ptr};
+
+#[cfg(feature = "metrics")]
+use crate::metrics::{self, MetricsEvent};
+use crate::{dealloc_inner, Algorithm, FlexRc};
+
+// *** IntoIter ***
+
+enum Source<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    // The last handle: elements are moved out, so its own `Drop` (which would drop them all again)
+    // must never run. We release it ourselves in `IntoIter::drop`
+    Unique(ManuallyDrop<FlexRc<META, META2, [T]>>),
+    // Other handles exist, so elements are cloned and the handle is dropped as usual
+    Shared(FlexRc<META, META2, [T]>),
+}
+
+/// A consuming iterator over a `FlexRc<[T]>`. If the handle was the only one to the data the
+/// elements are moved out (like `Vec::into_iter`), otherwise they are cloned
+pub struct IntoIter<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    source: Source<META, META2, T>,
+    // Elements not yet yielded
+    range: Range<usize>,
+}
+
+impl<META, META2, T> IntoIter<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn rc(&self) -> &FlexRc<META, META2, [T]> {
+        match &self.source {
+            Source::Unique(rc) => rc,
+            Source::Shared(rc) => rc,
+        }
+    }
+
+    #[inline]
+    fn data_ptr(&self) -> *mut T {
+        // SAFETY: Our handle keeps the allocation alive
+        unsafe { ptr::addr_of_mut!((*self.rc().ptr.as_ptr()).data) as *mut T }
+    }
+
+    /// Returns the elements that have not been yielded yet
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.rc().as_inner().data[self.range.clone()]
+    }
+
+    /// Returns true if elements are moved out instead of cloned
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        matches!(self.source, Source::Unique(_))
+    }
+
+    #[inline]
+    fn element(&self, idx: usize) -> T
+    where
+        T: Clone,
+    {
+        match &self.source {
+            // SAFETY: `idx` was just removed from `range`, so this element is read exactly once and
+            // never dropped in place
+            Source::Unique(_) => unsafe { ptr::read(self.data_ptr().add(idx)) },
+            Source::Shared(rc) => rc.as_inner().data[idx].clone(),
+        }
+    }
+}
+
+impl<META, META2, T> IntoIterator for FlexRc<META, META2, [T]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: Clone,
+{
+    type Item = T;
+    type IntoIter = IntoIter<META, META2, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        let range = 0..self.len();
+        // Since we own the only handle (and there are no weak ones), no new handle can appear
+        let source = if self.is_unique() {
+            Source::Unique(ManuallyDrop::new(self))
+        } else {
+            Source::Shared(self)
+        };
+
+        IntoIter { source, range }
+    }
+}
+
+impl<META, META2, T> Iterator for IntoIter<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: Clone,
+{
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let idx = self.range.next()?;
+        Some(self.element(idx))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<META, META2, T> DoubleEndedIterator for IntoIter<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: Clone,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let idx = self.range.next_back()?;
+        Some(self.element(idx))
+    }
+}
+
+impl<META, META2, T> ExactSizeIterator for IntoIter<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: Clone,
+{
+}
+
+impl<META, META2, T> FusedIterator for IntoIter<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: Clone,
+{
+}
+
+impl<META, META2, T> fmt::Debug for IntoIter<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("IntoIter").field(&self.as_slice()).finish()
+    }
+}
+
+impl<META, META2, T> Drop for IntoIter<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    fn drop(&mut self) {
+        let Source::Unique(rc) = &self.source else {
+            return;
+        };
+
+        // Frees the allocation even if dropping one of the remaining elements panics
+        struct Release<'a, META, META2, T>(&'a FlexRc<META, META2, [T]>)
+        where
+            META: Algorithm<META, META2>,
+            META2: Algorithm<META2, META>;
+
+        impl<META, META2, T> Drop for Release<'_, META, META2, T>
+        where
+            META: Algorithm<META, META2>,
+            META2: Algorithm<META2, META>,
+        {
+            fn drop(&mut self) {
+                // SAFETY: The strong count already reached zero and the elements are gone
+                unsafe {
+                    if self.0.as_inner().metadata.drop_weak() {
+                        dealloc_inner(self.0.ptr);
+                    }
+                }
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        metrics::record::<[T]>(MetricsEvent::Drop, rc.allocation_size());
+
+        // Same steps as `FlexRc::drop`, except only the elements not yet moved out are dropped
+        if rc.as_inner().metadata.drop() {
+            let _release = Release(rc);
+            let remaining = ptr::slice_from_raw_parts_mut(
+                // SAFETY: `range` is always within the slice
+                unsafe { self.data_ptr().add(self.range.start) },
+                self.range.len(),
+            );
+            // SAFETY: These elements were never read out, and nothing else can reach them
+            unsafe { ptr::drop_in_place(remaining) };
+        }
+    }
+}
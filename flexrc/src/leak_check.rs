@@ -0,0 +1,49 @@
+//! Counts live allocations so tests can catch handles that are never freed (typically reference
+//! cycles, which like `Rc` this crate can't collect). Counting only happens in debug builds, so it
+//! costs nothing in release builds, where `live_allocations` always returns zero. Rust offers no
+//! portable hook to run at program exit, so checks are made by calling `assert_no_leaks`
+//!
+//! ```
+//! let rc = flexrc::SharedRc::new(5);
+//! drop(rc);
+//! flexrc::assert_no_leaks();
+//! ```
+
+#[cfg(debug_assertions)]
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(debug_assertions)]
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+// *** Leak check ***
+
+/// Returns the number of allocations (from any thread) that have been created but not yet freed.
+/// Always zero in release builds
+#[inline]
+pub fn live_allocations() -> usize {
+    #[cfg(debug_assertions)]
+    return LIVE.load(Ordering::Acquire);
+    #[cfg(not(debug_assertions))]
+    0
+}
+
+/// Panics if any allocation is still alive. Since the count is global, call this only once other
+/// threads are done with their handles. Never panics in release builds
+#[track_caller]
+#[inline]
+pub fn assert_no_leaks() {
+    let live = live_allocations();
+    assert!(live == 0, "{live} flexrc allocation(s) still alive");
+}
+
+#[inline(always)]
+pub(crate) fn alloc_created() {
+    #[cfg(debug_assertions)]
+    LIVE.fetch_add(1, Ordering::Relaxed);
+}
+
+#[inline(always)]
+pub(crate) fn alloc_freed() {
+    #[cfg(debug_assertions)]
+    LIVE.fetch_sub(1, Ordering::Release);
+}
@@ -3,20 +3,140 @@
 extern crate alloc;
 
 mod algorithm;
-#[cfg(test)]
+#[cfg(not(feature = "str_deref"))]
+mod buf;
+mod builder;
+mod by_address;
+mod bytes;
+mod cell;
+mod family;
+#[cfg(feature = "ffi")]
+mod ffi;
+#[cfg(feature = "std")]
+mod interner;
+mod iter;
+#[cfg(feature = "leak_check")]
+mod leak_check;
+#[cfg(all(test, loom))]
+mod loom_tests;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "bytemuck")]
+mod pod;
+#[cfg(feature = "pool")]
+mod pool;
+#[cfg(feature = "zeroize")]
+mod secret;
+mod share;
+#[cfg(all(test, not(loom)))]
 mod tests;
+mod total_cmp;
+mod uninit;
+mod unique;
+#[cfg(feature = "weak")]
+mod weak;
+#[cfg(feature = "weak")]
+mod weak_list;
 
 pub use algorithm::*;
+#[cfg(not(feature = "str_deref"))]
+pub use buf::*;
+pub use builder::*;
+pub use by_address::*;
+pub use bytes::*;
+pub use cell::*;
+#[cfg(feature = "ffi")]
+pub use ffi::*;
+#[cfg(feature = "std")]
+pub use interner::*;
+pub use iter::*;
+#[cfg(feature = "leak_check")]
+pub use leak_check::*;
+#[cfg(feature = "metrics")]
+pub use metrics::*;
+#[cfg(feature = "zeroize")]
+pub use secret::*;
+pub use share::*;
+pub use total_cmp::*;
+pub use uninit::*;
+pub use unique::*;
+#[cfg(feature = "weak")]
+pub use weak::*;
+#[cfg(feature = "weak")]
+pub use weak_list::*;
 
-use alloc::alloc::{alloc, handle_alloc_error};
+use alloc::alloc::{alloc, dealloc, handle_alloc_error, realloc};
+#[cfg(feature = "str_deref")]
+use alloc::borrow::Cow;
 use alloc::boxed::Box;
 use alloc::str;
-use core::alloc::Layout;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::alloc::{Layout, LayoutError};
+use core::any::Any;
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::{Hash, Hasher};
 use core::marker::PhantomData;
-use core::ops::Deref;
+use core::ops::{Add, Deref};
+#[cfg(feature = "track_location")]
+use core::panic::Location;
 use core::ptr::NonNull;
 use core::{mem, ptr};
 
+use static_assertions::const_assert_eq;
+
+// *** AllocError ***
+
+/// The error returned by fallible constructors when the requested size is too large to be
+/// represented or the allocator is out of memory
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocError;
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("memory allocation failed")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AllocError {}
+
+// *** ConvertError ***
+
+/// The reason a conversion to the other handle type of a pair failed, carrying the original handle
+/// so the caller can retry later or fall back to a copy (`into_other`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConvertError<H> {
+    /// Other references (strong or weak) to the data exist
+    NotUnique(H),
+    /// Hybrid only: a local handle already exists. With `track_threads` it is on another thread,
+    /// otherwise it may be on any thread (including this one)
+    LocalTaken(H),
+}
+
+impl<H> ConvertError<H> {
+    /// Returns the original handle
+    #[inline]
+    pub fn into_inner(self) -> H {
+        match self {
+            ConvertError::NotUnique(handle) | ConvertError::LocalTaken(handle) => handle,
+        }
+    }
+}
+
+impl<H> fmt::Display for ConvertError<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConvertError::NotUnique(_) => "other references to the data exist",
+            ConvertError::LocalTaken(_) => "a local handle to the data already exists",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<H: fmt::Debug> std::error::Error for ConvertError<H> {}
+
 // *** FlexRcInner ***
 
 // MUST ensure both `Rc` and `Arc` have identical memory layout
@@ -25,30 +145,185 @@ use core::{mem, ptr};
 pub struct FlexRcInner<META, META2, T: ?Sized> {
     metadata: META,
     phantom: PhantomData<META2>,
+    // Where the allocation was made, for debugging leaked or unexpectedly shared handles
+    #[cfg(feature = "track_location")]
+    location: &'static Location<'static>,
     data: T,
 }
 
+// Slice allocations only reserve room for the elements after a header laid out as
+// `FlexRcInner<META, META2, ()>` (see `slice_layout`), so `data` MUST stay the trailing field or the
+// slice path would write past the end of the allocation
+const_assert_eq!(
+    mem::offset_of!(FlexRcInner<LocalMeta, SharedMeta, u8>, data),
+    mem::size_of::<FlexRcInner<LocalMeta, SharedMeta, ()>>()
+);
+const_assert_eq!(
+    mem::offset_of!(
+        FlexRcInner<HybridMeta<LocalMode>, HybridMeta<SharedMode>, u8>,
+        data
+    ),
+    mem::size_of::<FlexRcInner<HybridMeta<LocalMode>, HybridMeta<SharedMode>, ()>>()
+);
+
 impl<META, META2, T> FlexRcInner<META, META2, T>
 where
     META: Algorithm<META, META2>,
 {
     #[inline]
-    fn new(data: T) -> Self {
+    #[cfg_attr(feature = "track_location", track_caller)]
+    fn with_meta(data: T, metadata: META) -> Self {
         Self {
-            metadata: META::create(),
+            metadata,
             phantom: PhantomData,
+            #[cfg(feature = "track_location")]
+            location: Location::caller(),
             data,
         }
     }
 }
 
-impl<META, META2, T> FlexRcInner<META, META2, [mem::MaybeUninit<T>]> {
+// Inner with a slice of not yet initialized elements (only used while allocating)
+type UninitSliceInner<META, META2, T> = FlexRcInner<META, META2, [mem::MaybeUninit<T>]>;
+
+impl<META, META2, T> UninitSliceInner<META, META2, T> {
+    /// # Safety
+    /// All elements of the slice must have been initialized
+    #[inline]
+    unsafe fn assume_init(inner: NonNull<Self>) -> NonNull<FlexRcInner<META, META2, [T]>> {
+        // SAFETY: We just cast away `MaybeUninit<T>` which is guaranteed to be layout/alignment
+        // identical to `T` (the fat pointer length carries over unchanged)
+        NonNull::new_unchecked(inner.as_ptr() as *mut FlexRcInner<META, META2, [T]>)
+    }
+}
+
+// Writes the elements of a new slice one at a time. If dropped before `finish` (for example due to
+// a panic while producing an element), the elements written so far are dropped and the allocation
+// is freed
+struct SliceWriter<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    inner: NonNull<UninitSliceInner<META, META2, T>>,
+    len: usize,
+    written: usize,
+}
+
+impl<META, META2, T> SliceWriter<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    fn new(len: usize) -> Self {
+        Self {
+            inner: FlexRc::<META, META2, [T]>::new_slice_uninit_inner(len),
+            len,
+            written: 0,
+        }
+    }
+
+    #[inline(always)]
+    fn data(&self) -> *mut T {
+        // SAFETY: Only computes the address, never forming a reference to the uninitialized data
+        unsafe { ptr::addr_of_mut!((*self.inner.as_ptr()).data) as *mut T }
+    }
+
+    #[inline]
+    fn push(&mut self, value: T) {
+        assert!(
+            self.written < self.len,
+            "more elements than the slice length"
+        );
+
+        // SAFETY: We just checked there is room left for this element
+        unsafe { self.data().add(self.written).write(value) };
+        self.written += 1;
+    }
+
     #[inline]
-    unsafe fn assume_init(&mut self) -> &mut FlexRcInner<META, META2, [T]> {
-        // SAFETY: We hold an exclusive borrow and we just cast away `MaybeUninit<T>` which is
-        // guaranteed to be layout/alignment identical to `T`
-        &mut *(self as *mut Self as *mut FlexRcInner<META, META2, [T]>)
+    fn finish(self) -> FlexRc<META, META2, [T]> {
+        assert_eq!(
+            self.written, self.len,
+            "fewer elements than the slice length"
+        );
+
+        // All elements are written, so skip the cleanup
+        let this = mem::ManuallyDrop::new(self);
+
+        // SAFETY: Every element was initialized
+        unsafe { FlexRc::from_inner(FlexRcInner::assume_init(this.inner)) }
+    }
+}
+
+impl<META, META2, T> Drop for SliceWriter<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    fn drop(&mut self) {
+        // SAFETY: Exactly `written` elements were initialized and nothing else can reference the
+        // allocation yet
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.data(), self.written));
+            dealloc_inner(self.inner);
+        }
+    }
+}
+
+/// # Safety
+/// `ptr` must point to a live allocation whose data has already been dropped (or was never
+/// initialized) and there must be no references of any kind left to it. It must also have come
+/// from the global allocator, which every allocation path in this crate uses (`Box::new` for sized
+/// data, `alloc`/`realloc` for slices). Allocations carry no allocator tag, so an API that accepts
+/// outside pointers or lets users pick an allocator has to uphold this itself.
+///
+/// Only the header alignment is checked in debug builds. `GlobalAlloc` has no way to ask whether
+/// it owns a pointer, and a debug-only tag in the header would grow every allocation and make the
+/// `#[repr(C)]` layout of `FlexRcInner` differ between debug and release builds
+#[inline]
+unsafe fn dealloc_inner<META, META2, T: ?Sized>(ptr: NonNull<FlexRcInner<META, META2, T>>) {
+    // Checked before `for_value` since a misaligned reference would itself be UB
+    debug_assert_eq!(
+        ptr.as_ptr() as *mut u8 as usize % mem::align_of::<FlexRcInner<META, META2, ()>>(),
+        0,
+        "pointer is misaligned, so it can't be from a FlexRc allocation"
+    );
+    // Size and alignment only depend on the pointer metadata (slice length or vtable) which is still
+    // valid even though the data itself has been dropped
+    let layout = Layout::for_value(ptr.as_ref());
+    #[cfg(feature = "leak_check")]
+    leak_check::alloc_freed();
+    #[cfg(feature = "metrics")]
+    metrics::record::<T>(MetricsEvent::Dealloc, layout.size());
+    #[cfg(feature = "pool")]
+    if pool::give(ptr.cast(), layout) {
+        return;
     }
+    dealloc(ptr.as_ptr() as *mut u8, layout);
+}
+
+// The allocator can't serve (and `Layout` can't describe) more than `isize::MAX` bytes. `Layout`
+// would reject such a size as well, but checking the elements up front lets us say why
+#[inline]
+fn slice_too_large<T>(len: usize) -> bool {
+    match len.checked_mul(mem::size_of::<T>()) {
+        Some(size) => size > isize::MAX as usize,
+        None => true,
+    }
+}
+
+#[inline]
+fn slice_layout<META, META2, T>(len: usize) -> Result<Layout, LayoutError> {
+    let array_layout = Layout::array::<mem::MaybeUninit<T>>(len)?;
+
+    // Use () (size 0) because we will get the whole size from above when extending
+    Ok(Layout::new::<FlexRcInner<META, META2, ()>>()
+        .extend(array_layout)?
+        .0
+        .pad_to_align())
 }
 
 // *** FlexRc ***
@@ -71,14 +346,51 @@ where
     META2: Algorithm<META2, META>,
 {
     #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
     pub fn new(data: T) -> Self {
-        let boxed = Box::new(FlexRcInner::new(data));
+        // SAFETY: A freshly created meta describes exactly one strong reference
+        unsafe { Self::new_with_meta(data, META::create()) }
+    }
+
+    /// Same as `new`, but uses `meta` as the initial metadata instead of `META::create()`, so an
+    /// algorithm can carry caller provided state (such as a tag or generation)
+    ///
+    /// # Safety
+    /// `meta` must describe a single strong reference and nothing else (no weak references, no
+    /// local present bit, etc.), exactly as `create` would. The counts are trusted as is, so any
+    /// other state would free the data while handles to it still exist, or never free it at all
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub unsafe fn new_with_meta(data: T, meta: META) -> Self {
+        debug_assert!(
+            meta.is_unique(),
+            "meta must describe a single strong reference"
+        );
+        let boxed = Box::new(FlexRcInner::with_meta(data, meta));
+        #[cfg(feature = "leak_check")]
+        leak_check::alloc_created();
+        #[cfg(feature = "metrics")]
+        metrics::record::<T>(
+            MetricsEvent::New,
+            mem::size_of::<FlexRcInner<META, META2, T>>(),
+        );
 
         // SAFETY: `new_unchecked` is guaranteed to receive a valid pointer
         Self::from_inner(unsafe { NonNull::new_unchecked(Box::into_raw(boxed)) })
     }
 
+    /// Moves `data` into a new allocation. The same as `new` (and `From<T>`), named to pair with
+    /// `from_ref`: prefer this whenever the value is already owned, since it never clones
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn from_owned(data: T) -> Self {
+        Self::new(data)
+    }
+
+    /// Clones `data` into a new allocation. Only use this when all you have is a borrow: an owned
+    /// value can be moved in with `from_owned` instead
     #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
     pub fn from_ref(data: &T) -> Self
     where
         T: Clone,
@@ -86,26 +398,56 @@ where
         Self::new(data.clone())
     }
 
+    /// Moves the value out of `b` into a new allocation. The box allocation has no room for the
+    /// reference count header so the value is copied (bytewise, never cloned), after which only
+    /// the box allocation is freed
+    // The caller already has a box, so taking one is the point (the lint wants a plain `T`)
+    #[allow(clippy::boxed_local)]
     #[inline]
-    fn is_unique(&self) -> bool {
-        self.as_inner().metadata.is_unique()
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn from_box(b: Box<T>) -> Self {
+        Self::new(*b)
     }
 
+    /// Swaps in `new` and returns the old value if this is the only handle to the data, reusing
+    /// the allocation. When shared it never allocates a new handle (the other handles could not
+    /// observe the new value anyway): it fails and hands `new` back instead
     #[inline]
-    pub fn get_mut(&mut self) -> Option<&mut T> {
-        if self.is_unique() {
-            // SAFETY: Since this is the unique owner, we can be assured we are only giving out one `&mut`
-            unsafe { Some(self.get_mut_unchecked()) }
-        } else {
-            None
+    pub fn replace(&mut self, new: T) -> Result<T, T> {
+        match self.get_mut() {
+            Some(data) => Ok(mem::replace(data, new)),
+            None => Err(new),
         }
     }
 
-    /// # Safety
-    /// The user is trusted they are to be the sole owner before calling this (typically at init time)
+    /// Returns a mutable reference to the data, first moving this handle to a new allocation
+    /// holding `make(&data)` if other handles (strong or weak) exist. This is copy-on-write where
+    /// the copy can be cheaper than `Clone` (or `T` isn't `Clone` at all). The other handles keep
+    /// the original data
     #[inline]
-    pub unsafe fn get_mut_unchecked(&mut self) -> &mut T {
-        &mut (*self.ptr.as_ptr()).data
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn get_mut_or<F>(&mut self, make: F) -> &mut T
+    where
+        F: FnOnce(&T) -> T,
+    {
+        if !self.is_unique() {
+            *self = Self::new(make(&self.as_inner().data));
+        }
+
+        // SAFETY: Either we were already the only handle or we just replaced ours with a new one
+        unsafe { self.data_mut() }
+    }
+
+    /// Returns a mutable reference to the data, first cloning it into a new allocation for this
+    /// handle if other handles exist (like `Rc::make_mut`). Use `get_mut_or` to supply a cheaper
+    /// copy
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn to_mut_owned(&mut self) -> &mut T
+    where
+        T: Clone,
+    {
+        self.get_mut_or(T::clone)
     }
 }
 
@@ -113,74 +455,294 @@ impl<META, META2, T> FlexRc<META, META2, [T]>
 where
     META: Algorithm<META, META2>,
     META2: Algorithm<META2, META>,
-    T: Copy,
 {
+    /// # Safety
+    /// `layout` must have been returned from `slice_layout` for the same `len` and `T`
     #[inline]
-    fn new_slice_uninit_inner<'a>(
+    #[cfg_attr(feature = "track_location", track_caller)]
+    unsafe fn alloc_slice_inner(
+        layout: Layout,
         len: usize,
-    ) -> &'a mut FlexRcInner<META, META2, [mem::MaybeUninit<T>]> {
-        // Unwrap safety: All good as long as array length doesn't overflow in which case we panic
-        let array_layout = Layout::array::<mem::MaybeUninit<T>>(len).expect("valid array length");
-
-        // Unwrap safety: All good as long as same sort of overflow like above doesn't occur
-        // Use () (size 0) because we will get the whole size from above when extending
-        let layout = Layout::new::<FlexRcInner<META, META2, ()>>()
-            .extend(array_layout)
-            .expect("valid inner layout")
-            .0
-            .pad_to_align();
-
-        // SAFETY: We carefully crafted our layout to correct specifications above - but we check
+    ) -> Option<NonNull<UninitSliceInner<META, META2, T>>> {
+        // SAFETY: We carefully crafted our layout to correct specifications - but we check
         // for null below just in case we run out of memory
-        let ptr = unsafe { alloc(layout) } as *mut mem::MaybeUninit<T>;
+        let ptr = alloc(layout) as *mut mem::MaybeUninit<T>;
 
         // Ensure allocator didn't return NULL (docs say some allocators will)
-        let ptr = match ptr::NonNull::new(ptr) {
-            Some(ptr) => ptr.as_ptr(),
-            None => handle_alloc_error(layout),
-        };
+        let ptr = ptr::NonNull::new(ptr)?.as_ptr();
+        #[cfg(feature = "leak_check")]
+        leak_check::alloc_created();
+        #[cfg(feature = "metrics")]
+        metrics::record::<[T]>(MetricsEvent::New, layout.size());
 
         // This just makes a "fat pointer" setting the correct # of `T` entries in the metadata
-        let inner = ptr::slice_from_raw_parts(ptr, len)
-            as *mut FlexRcInner<META, META2, [mem::MaybeUninit<T>]>;
+        let inner = ptr::slice_from_raw_parts(ptr, len) as *mut UninitSliceInner<META, META2, T>;
 
         // Create our inner
-        // SAFETY: We made sure T is `Copy` and we carefully write out each field
-        unsafe {
-            ptr::write(&mut (*inner).metadata, META::create());
-            &mut (*inner)
+        // SAFETY: We carefully write out each field without ever forming a reference to the
+        // uninitialized memory (the data itself is allowed to stay uninitialized)
+        ptr::addr_of_mut!((*inner).metadata).write(META::create());
+        #[cfg(feature = "track_location")]
+        ptr::addr_of_mut!((*inner).location).write(Location::caller());
+        Some(NonNull::new_unchecked(inner))
+    }
+
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    fn new_slice_uninit_inner(len: usize) -> NonNull<UninitSliceInner<META, META2, T>> {
+        assert!(
+            !slice_too_large::<T>(len),
+            "slice of {len} elements exceeds isize::MAX bytes"
+        );
+        // Unwrap safety: All good as long as array length doesn't overflow in which case we panic
+        let layout = slice_layout::<META, META2, T>(len).expect("valid slice layout");
+
+        // SAFETY: Layout came from `slice_layout` for this `len`
+        match unsafe { Self::alloc_slice_inner(layout, len) } {
+            Some(inner) => inner,
+            None => handle_alloc_error(layout),
+        }
+    }
+
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    fn try_new_slice_uninit_inner(
+        len: usize,
+    ) -> Result<NonNull<UninitSliceInner<META, META2, T>>, AllocError> {
+        if slice_too_large::<T>(len) {
+            return Err(AllocError);
         }
+        let layout = slice_layout::<META, META2, T>(len).map_err(|_| AllocError)?;
+
+        // SAFETY: Layout came from `slice_layout` for this `len`
+        unsafe { Self::alloc_slice_inner(layout, len) }.ok_or(AllocError)
     }
 
     #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
     pub fn new_slice_uninit(len: usize) -> FlexRc<META, META2, [mem::MaybeUninit<T>]> {
+        FlexRc::from_inner(Self::new_slice_uninit_inner(len))
+    }
+
+    /// Returns the number of elements. This doesn't rely on `Deref`, so it works the same for
+    /// every element type and with or without the `str_deref` feature
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.as_inner().data.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Moves the elements of `v` into a new allocation and frees the `Vec` buffer. The elements
+    /// are still copied (bytewise) since the reference count header has to precede them, but
+    /// unlike `from_slice` this works for any `T` and the caller doesn't keep both copies alive.
+    /// Like `from_slice`, this is not available with the `str_deref` feature
+    #[cfg(not(feature = "str_deref"))]
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn from_vec(v: Vec<T>) -> Self {
+        Self::from_vec_priv(v)
+    }
+
+    /// Moves the elements of `b` into a new allocation and frees the box allocation. As with
+    /// `from_vec`, the elements are copied bytewise (never cloned). Like `from_slice`, this is not
+    /// available with the `str_deref` feature
+    #[cfg(not(feature = "str_deref"))]
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn from_boxed_slice(b: Box<[T]>) -> Self {
+        Self::from_vec_priv(b.into_vec())
+    }
+
+    /// Moves the elements of `arr` into a new allocation. Unlike `from_slice`, this works for any
+    /// `T` since nothing is cloned. Like `from_slice`, this is not available with the `str_deref`
+    /// feature
+    #[cfg(not(feature = "str_deref"))]
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn new_array<const N: usize>(arr: [T; N]) -> Self {
+        let arr = mem::ManuallyDrop::new(arr);
+        let inner = Self::new_slice_uninit_inner(N);
+
+        // SAFETY: The new allocation has room for exactly `N` elements, and the array won't drop
+        // the elements we moved out of it
+        unsafe {
+            ptr::copy_nonoverlapping(
+                arr.as_ptr(),
+                ptr::addr_of_mut!((*inner.as_ptr()).data) as *mut T,
+                N,
+            );
+            Self::from_inner(FlexRcInner::assume_init(inner))
+        }
+    }
+
+    /// Clones the elements of `data` into a new allocation. Unlike `from_slice`, this works for any
+    /// `T: Clone`. If a clone panics, the clones made so far are dropped and the allocation is
+    /// freed before unwinding continues. Like `from_slice`, this is not available with the
+    /// `str_deref` feature
+    #[cfg(not(feature = "str_deref"))]
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn from_slice_cloned(data: &[T]) -> Self
+    where
+        T: Clone,
+    {
+        let mut writer = SliceWriter::new(data.len());
+        for elem in data {
+            writer.push(elem.clone());
+        }
+        writer.finish()
+    }
+
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    fn from_vec_priv(mut v: Vec<T>) -> Self {
+        let len = v.len();
         let inner = Self::new_slice_uninit_inner(len);
-        FlexRc::from_inner(inner.into())
+
+        // SAFETY: The new allocation has room for exactly `len` elements. Setting the `Vec` length
+        // to zero first means it will only free its buffer and not drop the moved elements
+        unsafe {
+            v.set_len(0);
+            ptr::copy_nonoverlapping(
+                v.as_ptr(),
+                ptr::addr_of_mut!((*inner.as_ptr()).data) as *mut T,
+                len,
+            );
+            Self::from_inner(FlexRcInner::assume_init(inner))
+        }
+    }
+
+    /// Creates a new slice holding `f` applied to each element, writing the results directly into
+    /// a single new allocation (no intermediate `Vec`). If `f` panics, the elements written so far
+    /// are dropped and the allocation is freed. Like `from_slice`, this is not available with the
+    /// `str_deref` feature
+    #[cfg(not(feature = "str_deref"))]
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn map_to<U, F>(&self, mut f: F) -> FlexRc<META, META2, [U]>
+    where
+        F: FnMut(&T) -> U,
+    {
+        let src = &self.as_inner().data;
+        let mut writer = SliceWriter::new(src.len());
+        for elem in src {
+            writer.push(f(elem));
+        }
+        writer.finish()
+    }
+
+    /// Creates a new slice holding clones of the elements matching `predicate`. The matches are
+    /// counted first so the result needs only a single allocation of the exact size, which means
+    /// `predicate` is called twice per element and must give the same answer both times (else
+    /// this panics). Like `from_slice`, this is not available with the `str_deref` feature
+    #[cfg(not(feature = "str_deref"))]
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn filtered<F>(&self, mut predicate: F) -> Self
+    where
+        T: Clone,
+        F: FnMut(&T) -> bool,
+    {
+        let src = &self.as_inner().data;
+        let len = src.iter().filter(|elem| predicate(elem)).count();
+
+        let mut writer = SliceWriter::new(len);
+        for elem in src.iter().filter(|elem| predicate(elem)) {
+            writer.push(elem.clone());
+        }
+        writer.finish()
+    }
+
+    // Clones our elements into a new allocation with the other type of metadata for the pair
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    fn clone_slice_to_other(&self) -> FlexRc<META2, META, [T]>
+    where
+        T: Clone,
+    {
+        let src = &self.as_inner().data;
+        let mut writer = SliceWriter::new(src.len());
+        for elem in src {
+            writer.push(elem.clone());
+        }
+        writer.finish()
+    }
+
+    /// Same as `into_other`, but for slices: if the conversion can't be made in place, the
+    /// elements are cloned into a new allocation of the same length
+    #[inline]
+    pub fn into_other_slice(self) -> FlexRc<META2, META, [T]>
+    where
+        T: Clone,
+    {
+        match self.try_into_other() {
+            Ok(other) => other,
+            Err(this) => this.clone_slice_to_other(),
+        }
+    }
+
+    /// Same as `to_other`, but for slices: if the new instance can't share this allocation, the
+    /// elements are cloned into a new allocation of the same length
+    #[inline]
+    pub fn to_other_slice(&self) -> FlexRc<META2, META, [T]>
+    where
+        T: Clone,
+    {
+        match self.try_to_other() {
+            Ok(other) => other,
+            Err(this) => this.clone_slice_to_other(),
+        }
     }
+}
 
+impl<META, META2, T> FlexRc<META, META2, [T]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: Copy,
+{
     // This is not safe IF str deref feature is on because there is no guarantee that `str` bytes
     // came from well formed UTF
     #[cfg(not(feature = "str_deref"))]
     #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
     pub fn from_slice(data: &[T]) -> Self {
         Self::from_slice_priv(data)
     }
 
     #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
     fn from_slice_priv(data: &[T]) -> Self {
-        let inner = Self::new_slice_uninit_inner(data.len());
+        Self::init_from_slice(Self::new_slice_uninit_inner(data.len()), data)
+    }
 
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    fn try_from_slice_priv(data: &[T]) -> Result<Self, AllocError> {
+        Ok(Self::init_from_slice(
+            Self::try_new_slice_uninit_inner(data.len())?,
+            data,
+        ))
+    }
+
+    // `inner` MUST have been allocated with exactly `data.len()` entries
+    #[inline]
+    fn init_from_slice(inner: NonNull<UninitSliceInner<META, META2, T>>, data: &[T]) -> Self {
         // SAFETY: We made sure T is `Copy` and we only copy the correct length
         unsafe {
             ptr::copy_nonoverlapping(
                 data.as_ptr(),
-                &mut (*inner).data as *mut [mem::MaybeUninit<T>] as *mut [T] as *mut T,
+                ptr::addr_of_mut!((*inner.as_ptr()).data) as *mut T,
                 data.len(),
             );
         }
 
         // Now that we are initialized, dump the MaybeUninit wrapper
-        unsafe { Self::from_inner(inner.assume_init().into()) }
+        unsafe { Self::from_inner(FlexRcInner::assume_init(inner)) }
     }
 }
 
@@ -192,16 +754,66 @@ where
     /// # Safety
     /// We have unique ownership. We are trusting the user that this memory has been initialized
     /// (thus why it is an unsafe function)
+    /// With `str_deref`, `[u8]` data must also be valid UTF-8, since it derefs to `str`
     #[inline]
     pub unsafe fn assume_init(self) -> FlexRc<META, META2, [T]> {
-        FlexRc::from_inner(
-            // Avoid drop to ensure no ref count decrement
-            mem::ManuallyDrop::new(self)
-                .ptr
-                .as_mut()
-                .assume_init()
-                .into(),
-        )
+        // Other handles would have been able to observe the uninitialized elements (`UninitRc`
+        // rules this out at compile time)
+        debug_assert!(self.is_unique(), "assume_init on a shared handle");
+
+        // Avoid drop to ensure no ref count decrement
+        let this = mem::ManuallyDrop::new(self);
+        FlexRc::from_inner(FlexRcInner::assume_init(this.ptr))
+    }
+
+    /// Same as `assume_init`, but shrinks the slice to its first `len` elements, releasing the rest
+    /// of the allocation. This allows finalizing a buffer that was filled incrementally (for example,
+    /// by an `io::Write` style builder) with fewer elements than were allocated up front.
+    ///
+    /// # Safety
+    /// We have unique ownership and we are trusting the user that the first `len` elements have been
+    /// initialized. The tail is released without running any destructors, so any initialized
+    /// elements there are leaked. Panics if `len` exceeds the current length. With `str_deref`,
+    /// `[u8]` data must also be valid UTF-8
+    pub unsafe fn set_len_and_assume_init(mut self, len: usize) -> FlexRc<META, META2, [T]> {
+        assert!(len <= self.len(), "new length exceeds current length");
+        self.resize(len);
+        self.assume_init()
+    }
+
+    /// Moves the elements into an allocation with room for exactly `len` elements (growing or
+    /// shrinking), keeping the header and the first `min(len, old_len)` elements. If this panics,
+    /// the handle is left unchanged
+    ///
+    /// # Safety
+    /// We must have unique ownership. Any initialized elements past `len` are leaked
+    pub(crate) unsafe fn resize(&mut self, len: usize) {
+        let old_layout = Layout::for_value(self.as_inner());
+        assert!(
+            !slice_too_large::<T>(len),
+            "slice of {len} elements exceeds isize::MAX bytes"
+        );
+        // Unwrap safety: All good as long as array length doesn't overflow in which case we panic
+        let layout = slice_layout::<META, META2, T>(len).expect("valid slice layout");
+
+        let ptr = if layout.size() == old_layout.size() {
+            self.ptr.as_ptr() as *mut u8
+        } else {
+            // SAFETY: We are the unique owner, so nobody else can observe the allocation moving
+            match ptr::NonNull::new(realloc(
+                self.ptr.as_ptr() as *mut u8,
+                old_layout,
+                layout.size(),
+            )) {
+                Some(ptr) => ptr.as_ptr(),
+                None => handle_alloc_error(layout),
+            }
+        };
+
+        // This just makes a "fat pointer" setting the correct # of `T` entries in the metadata
+        let inner = ptr::slice_from_raw_parts_mut(ptr as *mut T, len)
+            as *mut UninitSliceInner<META, META2, T>;
+        self.ptr = NonNull::new_unchecked(inner);
     }
 }
 
@@ -210,53 +822,425 @@ where
     META: Algorithm<META, META2>,
     META2: Algorithm<META2, META>,
 {
-    // There isn't an agreed upon way at a low level to go from [u8] -> str DST.
-    // While casting may very well work forever, I decided to go the ultra safe
-    // route and store as [U8] and just convert via deref to str
+    // This stores the string as [u8] and (with the str deref feature) converts via deref to str.
+    // For a handle that holds `str` directly, see `FlexRc<str>` below
     #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
     pub fn from_str_ref(s: impl AsRef<str>) -> FlexRc<META, META2, [u8]> {
         FlexRc::from_slice_priv(s.as_ref().as_bytes())
     }
-}
 
-impl<META, META2, T> FlexRc<META, META2, T>
-where
-    META: Algorithm<META, META2>,
-    META2: Algorithm<META2, META>,
-    T: ?Sized,
-{
-    #[inline(always)]
-    fn from_inner(inner: NonNull<FlexRcInner<META, META2, T>>) -> Self {
-        Self {
-            ptr: inner,
-            phantom: PhantomData,
-        }
+    /// Validates that `bytes` are UTF-8 and, if so, copies them into a new instance
+    #[inline]
+    pub fn from_utf8(bytes: &[u8]) -> Result<FlexRc<META, META2, [u8]>, str::Utf8Error> {
+        str::from_utf8(bytes).map(Self::from_str_ref)
     }
 
-    #[inline(always)]
-    fn as_inner(&self) -> &FlexRcInner<META, META2, T> {
-        // SAFETY: As long as we have an instance, our pointer is guaranteed valid
-        unsafe { self.ptr.as_ref() }
+    /// Copies `bytes` into a new instance, replacing invalid UTF-8 sequences with `U+FFFD` like
+    /// `String::from_utf8_lossy`. Valid input is copied directly without an intermediate `String`
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn from_utf8_lossy(bytes: &[u8]) -> FlexRc<META, META2, [u8]> {
+        Self::from_str_ref(String::from_utf8_lossy(bytes))
     }
 
-    /// Try to convert this into a type with the other type of metadata for the pair (local -> shared,
-    /// or shared -> local). If it is possible it will return the new type, else it will fail and
-    /// return itself instead
+    /// Same as `from_str_ref`, but returns an error instead of panicking or aborting if the
+    /// allocation can't be made (for example, when building from untrusted input)
     #[inline]
-    pub fn try_into_other(self) -> Result<FlexRc<META2, META, T>, Self> {
-        let meta = &self.as_inner().metadata;
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn try_from_str_ref(s: impl AsRef<str>) -> Result<FlexRc<META, META2, [u8]>, AllocError> {
+        FlexRc::try_from_slice_priv(s.as_ref().as_bytes())
+    }
 
-        match meta.try_into_other(self.ptr.as_ptr()) {
-            Ok(inner) => {
-                // SAFETY: We are guaranteed to have a non-null pointer here
-                let inner = unsafe { NonNull::new_unchecked(inner) };
-                Ok(<FlexRc<META2, META, T>>::from_inner(inner))
+    // Copies each part in sequence into a single new allocation of `len` bytes
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    fn concat_priv<'a>(parts: impl Iterator<Item = &'a [u8]>, len: usize) -> Self {
+        let mut buf = Self::new_slice_uninit(len);
+
+        // SAFETY: We just created this so we are the only owner
+        let data = unsafe { buf.data_mut() };
+        let mut pos = 0;
+
+        for part in parts {
+            // SAFETY: `len` is the sum of the part lengths so the copy is in bounds, and
+            // `MaybeUninit<u8>` is layout identical to `u8`
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    part.as_ptr(),
+                    data.as_mut_ptr().add(pos) as *mut u8,
+                    part.len(),
+                );
             }
-            Err(_) => Err(self),
+            pos += part.len();
         }
+        debug_assert_eq!(pos, len);
+
+        // SAFETY: Every byte was written above
+        unsafe { buf.assume_init() }
     }
 
-    /// Try to convert this into a type with the other type of metadata for the pair (local -> shared,
+    /// Concatenates `parts` into a single new allocation, without an intermediate `Vec`. Like
+    /// `from_slice`, this is not available with the `str_deref` feature since the bytes may not be
+    /// valid UTF-8
+    #[cfg(not(feature = "str_deref"))]
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn concat(parts: &[&[u8]]) -> Self {
+        let len = parts.iter().map(|part| part.len()).sum();
+        Self::concat_priv(parts.iter().copied(), len)
+    }
+
+    /// Gathers the chunks into a single new allocation (the gather half of scatter-gather IO). The
+    /// iterator is only walked once, so the chunks themselves (not their bytes) are buffered until
+    /// the total length is known. Like `from_slice`, this is not available with the `str_deref`
+    /// feature since the bytes may not be valid UTF-8
+    #[cfg(not(feature = "str_deref"))]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn from_slices<I>(chunks: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut chunks = chunks.into_iter();
+
+        // No chunks or a single one need neither buffering nor copying in pieces
+        let Some(first) = chunks.next() else {
+            return Self::from_slice_priv(&[]);
+        };
+        let Some(second) = chunks.next() else {
+            return Self::from_slice_priv(first.as_ref());
+        };
+
+        let mut owned = Vec::with_capacity(chunks.size_hint().0 + 2);
+        owned.push(first);
+        owned.push(second);
+        owned.extend(chunks);
+
+        // `as_ref` is called exactly once per chunk, so the lengths can't change between summing
+        // them up and copying
+        let parts: Vec<&[u8]> = owned.iter().map(AsRef::as_ref).collect();
+        let len = parts
+            .iter()
+            .try_fold(0usize, |len, part| len.checked_add(part.len()))
+            .expect("capacity overflow");
+
+        if len == 0 {
+            Self::from_slice_priv(&[])
+        } else {
+            Self::concat_priv(parts.into_iter(), len)
+        }
+    }
+
+    /// Concatenates the bytes of `parts` into a single new allocation, without an intermediate
+    /// `Vec`. Since every handle is valid UTF-8 with the `str_deref` feature, so is the result
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn concat_rc(parts: &[&Self]) -> Self {
+        let len = parts.iter().map(|part| part.as_bytes().len()).sum();
+        Self::concat_priv(parts.iter().map(|part| part.as_bytes()), len)
+    }
+
+    /// Returns the underlying bytes. Unlike `Deref`, this is available regardless of whether the
+    /// `str_deref` feature is enabled
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.as_inner().data
+    }
+
+    /// Returns the underlying bytes as a `str` if they are valid UTF-8. Unlike `Deref`, this is
+    /// available regardless of whether the `str_deref` feature is enabled
+    #[inline]
+    pub fn as_str(&self) -> Result<&str, str::Utf8Error> {
+        str::from_utf8(self.as_bytes())
+    }
+
+    /// Borrows the string as a `Cow` for APIs that take `Cow<str>`, without copying
+    #[cfg(feature = "str_deref")]
+    #[inline]
+    pub fn as_cow_str(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+
+    /// Copies the string into an owned `Cow`, for APIs that need `Cow<'static, str>`
+    #[cfg(feature = "str_deref")]
+    #[inline]
+    pub fn into_cow_owned(self) -> Cow<'static, str> {
+        Cow::Owned(String::from(&*self))
+    }
+
+    /// Reads exactly `len` bytes from `reader` straight into a new allocation, avoiding an
+    /// intermediate `Vec`. On error the allocation is freed and the error is returned. Like
+    /// `from_slice`, this is not available with the `str_deref` feature since the bytes may not
+    /// be valid UTF-8
+    #[cfg(all(feature = "std", not(feature = "str_deref")))]
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn from_reader(reader: &mut impl std::io::Read, len: usize) -> std::io::Result<Self> {
+        let mut buf = Self::new_slice_uninit(len);
+
+        // SAFETY: We just created this so we are the only owner. `Read` may not be given
+        // uninitialized memory, so zero it first (`MaybeUninit<u8>` is layout identical to `u8`)
+        let bytes = unsafe {
+            let data = buf.data_mut();
+            ptr::write_bytes(data.as_mut_ptr(), 0, len);
+            &mut *(data as *mut [mem::MaybeUninit<u8>] as *mut [u8])
+        };
+        reader.read_exact(bytes)?;
+
+        // SAFETY: Every byte was zeroed above and then filled by the reader
+        Ok(unsafe { buf.assume_init() })
+    }
+}
+
+impl<META, META2> FlexRc<META, META2, str>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    // SAFETY: `str` and `[u8]` have the same layout and both fat pointers carry the length in
+    // bytes, so the cast only changes the type. The caller must ensure the bytes are valid UTF-8
+    #[inline]
+    unsafe fn from_bytes_unchecked(bytes: FlexRc<META, META2, [u8]>) -> Self {
+        // Avoid drop to ensure no ref count decrement
+        let bytes = mem::ManuallyDrop::new(bytes);
+        Self::from_inner(NonNull::new_unchecked(
+            bytes.ptr.as_ptr() as *mut FlexRcInner<META, META2, str>
+        ))
+    }
+
+    /// Same as `From<&str>`, but returns an error instead of panicking or aborting if the
+    /// allocation can't be made (for example, when building from untrusted input)
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn try_from_str(s: &str) -> Result<Self, AllocError> {
+        let bytes = FlexRc::try_from_slice_priv(s.as_bytes())?;
+        // SAFETY: The bytes came from a `str`
+        Ok(unsafe { Self::from_bytes_unchecked(bytes) })
+    }
+
+    /// Returns the length in bytes (same as `str::len`)
+    #[inline]
+    pub fn byte_len(&self) -> usize {
+        self.as_inner().data.len()
+    }
+
+    /// Returns the number of `char`s. Unlike `byte_len`, this has to walk the whole string
+    #[inline]
+    pub fn char_len(&self) -> usize {
+        self.as_inner().data.chars().count()
+    }
+
+    /// Converts back into a byte slice handle without copying
+    #[inline]
+    pub fn into_bytes(self) -> FlexRc<META, META2, [u8]> {
+        // Avoid drop to ensure no ref count decrement
+        let this = mem::ManuallyDrop::new(self);
+
+        // SAFETY: Same layout and fat pointer metadata as above, and any `str` is valid as `[u8]`
+        unsafe {
+            FlexRc::from_inner(NonNull::new_unchecked(
+                this.ptr.as_ptr() as *mut FlexRcInner<META, META2, [u8]>
+            ))
+        }
+    }
+}
+
+// *** Mutable access ***
+
+macro_rules! mut_access {
+    ($data:ty) => {
+        /// Returns a mutable reference to the data if this is the only handle to it. As with
+        /// `Arc::get_mut`, any weak handle also counts, since it could otherwise be upgraded while
+        /// the `&mut T` is alive
+        #[inline]
+        pub fn get_mut(&mut self) -> Option<&mut $data> {
+            if self.is_unique() {
+                // SAFETY: Since this is the unique owner, we can be assured we are only giving out one `&mut`
+                unsafe { Some(self.data_mut()) }
+            } else {
+                None
+            }
+        }
+
+        /// Same as `get_mut`, named for use where a `BorrowMut` impl might be expected. `BorrowMut`
+        /// is deliberately not implemented: it must always hand out a `&mut T`, which is only sound
+        /// while the handle is unique, so it would have to panic whenever the data is shared
+        #[inline]
+        pub fn try_borrow_mut(&mut self) -> Option<&mut $data> {
+            self.get_mut()
+        }
+
+        /// # Safety
+        /// The user is trusted they are to be the sole owner before calling this (typically at init time)
+        #[inline]
+        pub unsafe fn get_mut_unchecked(&mut self) -> &mut $data {
+            self.data_mut()
+        }
+    };
+}
+
+#[cfg(not(feature = "str_deref"))]
+impl<META, META2, T> FlexRc<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    mut_access!(T);
+}
+
+// With `str_deref`, `[u8]` data derefs to `str` and so must always stay valid UTF-8. Handing out
+// `&mut [u8]` would let safe code break that, so mutable access is limited to sized data (the
+// same reason `from_slice` and friends are unavailable) and uninitialized slices, which can only
+// become `[u8]` through the unsafe `assume_init`
+#[cfg(feature = "str_deref")]
+impl<META, META2, T> FlexRc<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    mut_access!(T);
+}
+
+#[cfg(feature = "str_deref")]
+impl<META, META2, T> FlexRc<META, META2, [mem::MaybeUninit<T>]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    mut_access!([mem::MaybeUninit<T>]);
+}
+
+impl<META, META2, T> FlexRc<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    #[inline(always)]
+    fn from_inner(inner: NonNull<FlexRcInner<META, META2, T>>) -> Self {
+        Self {
+            ptr: inner,
+            phantom: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    fn as_inner(&self) -> &FlexRcInner<META, META2, T> {
+        // SAFETY: As long as we have an instance, our pointer is guaranteed valid
+        unsafe { self.ptr.as_ref() }
+    }
+
+    #[inline]
+    fn is_unique(&self) -> bool {
+        self.as_inner().metadata.is_unique()
+    }
+
+    /// # Safety
+    /// Must be the sole owner. Unlike `get_mut_unchecked` this is available for all data, so
+    /// callers must also keep `[u8]` data valid UTF-8 when `str_deref` is enabled
+    #[inline(always)]
+    unsafe fn data_mut(&mut self) -> &mut T {
+        &mut (*self.ptr.as_ptr()).data
+    }
+
+    /// Fills every slot with a clone of this handle, raising the count once for all of them
+    /// instead of once per clone (a single `fetch_add` for `SharedRc`). Any handles already in the
+    /// slots are overwritten without being dropped. Returns the now initialized slots
+    #[inline]
+    pub fn scatter<'a>(&self, slots: &'a mut [mem::MaybeUninit<Self>]) -> &'a mut [Self] {
+        self.as_inner().metadata.clone_many(slots.len());
+
+        for slot in slots.iter_mut() {
+            #[cfg(feature = "metrics")]
+            metrics::record::<T>(MetricsEvent::Clone, self.allocation_size());
+            slot.write(Self::from_inner(self.ptr));
+        }
+
+        // SAFETY: Every slot was just written, and `MaybeUninit<Self>` is layout identical to `Self`
+        unsafe { &mut *(slots as *mut [mem::MaybeUninit<Self>] as *mut [Self]) }
+    }
+
+    /// Returns true if both handles point to the same allocation
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.ptr.as_ptr() as *const u8 == other.ptr.as_ptr() as *const u8
+    }
+
+    /// Returns the address of the data, for use as an identity key (for example in a cache). It is
+    /// stable across `try_into_other` and the other cheap conversions, since those never move the
+    /// data, but not across conversions that fall back to copying it (such as `into_other` when not
+    /// unique)
+    #[inline]
+    pub fn identity(&self) -> usize {
+        // Only use the address and drop any fat pointer metadata
+        &self.as_inner().data as *const T as *const u8 as usize
+    }
+
+    /// Returns the number of strong references to this allocation. For the hybrid algorithm, when
+    /// called on a shared handle, all local handles are counted as a single reference. Shared
+    /// counts are read with a `SeqCst` load (so at least `Acquire`) by every algorithm
+    #[inline]
+    pub fn strong_count(&self) -> usize {
+        self.as_inner().metadata.strong_count()
+    }
+
+    /// Same as `strong_count`, but uses a `Relaxed` load for cheap, approximate observation. The
+    /// value may be stale and is only suitable for metrics/logging, never for control flow. It is
+    /// a plain load (never a read-modify-write), so monitoring threads can poll it without taking
+    /// the cache line away from the threads cloning and dropping handles
+    #[inline]
+    pub fn strong_count_relaxed(&self) -> usize {
+        self.as_inner().metadata.strong_count_relaxed()
+    }
+
+    /// Returns the source location of the constructor call that made this allocation. Constructors
+    /// that allocate a new copy on behalf of another method (such as `into_other` when not unique)
+    /// report the location of that method call
+    #[cfg(feature = "track_location")]
+    #[inline]
+    pub fn alloc_location(&self) -> &'static Location<'static> {
+        self.as_inner().location
+    }
+
+    /// Returns the total size in bytes of the backing allocation, including the reference count
+    /// header and (for slices) every element
+    #[inline]
+    pub fn allocation_size(&self) -> usize {
+        // Same layout `dealloc_inner` frees with
+        Layout::for_value(self.as_inner()).size()
+    }
+
+    /// Returns true if `try_into_other` would currently succeed, without performing the conversion.
+    /// This never changes any state, so if other handles live on other threads the answer is only
+    /// a hint that may be stale by the time a conversion is attempted
+    #[inline]
+    pub fn can_convert(&self) -> bool {
+        self.as_inner().metadata.can_convert()
+    }
+
+    /// Try to convert this into a type with the other type of metadata for the pair (local -> shared,
+    /// or shared -> local). If it is possible it will return the new type, else it will fail and
+    /// return itself instead
+    #[inline]
+    pub fn try_into_other(self) -> Result<FlexRc<META2, META, T>, Self> {
+        let meta = &self.as_inner().metadata;
+
+        match meta.try_into_other(self.ptr.as_ptr()) {
+            Ok(inner) => {
+                // Our reference now belongs to the new instance, so we must not drop it
+                mem::forget(self);
+
+                // SAFETY: We are guaranteed to have a non-null pointer here
+                let inner = unsafe { NonNull::new_unchecked(inner) };
+                Ok(<FlexRc<META2, META, T>>::from_inner(inner))
+            }
+            Err(_) => Err(self),
+        }
+    }
+
+    /// Try to convert this into a type with the other type of metadata for the pair (local -> shared,
     /// or shared -> local). If it is possible it will return the new type without additional copy
     /// or allocation, but if not possible, it will clone the underlying data and return a new Rc
     #[inline]
@@ -297,11 +1281,162 @@ where
     {
         match self.try_to_other() {
             Ok(other) => other,
-            Err(this) => <FlexRc<META2, META, T>>::from_ref(&*this),
+            Err(this) => <FlexRc<META2, META, T>>::from_ref(this),
+        }
+    }
+
+    /// Applies `f` to the data and returns the result in a new allocation, releasing `this`.
+    /// Unlike `get_mut`, which changes the data in place (and only when unique), this always
+    /// allocates, even when unique and `U` is the same type as `T`, and never touches the original
+    /// data. It is an associated function (`FlexRc::map(rc, f)`) so it can't shadow a `map` method
+    /// of `T`
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn map<U, F>(this: Self, f: F) -> FlexRc<META, META2, U>
+    where
+        F: FnOnce(&T) -> U,
+    {
+        FlexRc::new(f(&this.as_inner().data))
+    }
+
+    /// Same as `map`, but for a fallible `f`. Nothing is allocated unless `f` succeeds, and `this`
+    /// is released either way
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn try_map<U, E, F>(this: Self, f: F) -> Result<FlexRc<META, META2, U>, E>
+    where
+        F: FnOnce(&T) -> Result<U, E>,
+    {
+        // Not `map(FlexRc::new)`, which would lose the caller location for `track_location`
+        match f(&this.as_inner().data) {
+            Ok(data) => Ok(FlexRc::new(data)),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Moves the value out of `b` into a new allocation, for any unsized `T` (most usefully a trait
+    /// object, since unsizing coercions of `FlexRc` itself need nightly). The value is copied
+    /// bytewise (never cloned), after which only the box allocation is freed. `Deref` is only
+    /// implemented for sized data (and slices/strings), so access a trait object through
+    /// `Borrow`. Like `from_slice`, this is not available with the `str_deref` feature, since `T`
+    /// could be `[u8]` holding invalid UTF-8.
+    ///
+    /// The new handle reuses the box pointer's metadata by assuming the address is the first word
+    /// of a fat pointer (checked in debug builds). Slices and trait objects are both covered by
+    /// tests that run under Miri
+    #[cfg(not(feature = "str_deref"))]
+    #[inline]
+    #[cfg_attr(feature = "track_location", track_caller)]
+    pub fn from_box_dyn(b: Box<T>) -> Self {
+        let src = Box::into_raw(b);
+        // SAFETY: The box pointer is valid until we free it below
+        let value_layout = Layout::for_value(unsafe { &*src });
+
+        // Same as `slice_layout`: the header is laid out as `FlexRcInner<META, META2, ()>` and
+        // `data` is the trailing field, so `offset` matches what `repr(C)` gives the full type
+        let (layout, offset) = Layout::new::<FlexRcInner<META, META2, ()>>()
+            .extend(value_layout)
+            .expect("valid layout");
+        let layout = layout.pad_to_align();
+
+        // SAFETY: The layout has a non-zero size since it always includes the metadata
+        let ptr = unsafe { alloc(layout) };
+        if ptr.is_null() {
+            handle_alloc_error(layout);
+        }
+        #[cfg(feature = "leak_check")]
+        leak_check::alloc_created();
+        #[cfg(feature = "metrics")]
+        metrics::record::<T>(MetricsEvent::New, layout.size());
+
+        // Keep the fat pointer metadata (vtable or length) of the box pointer, but point at the new
+        // allocation instead. This relies on the address being the first word of a fat pointer,
+        // which rustc does but doesn't guarantee (std `Rc` did the same before the pointer
+        // metadata API existed). Once `ptr::from_raw_parts` is stable it should replace this
+        let mut inner = src as *mut FlexRcInner<META, META2, T>;
+        // SAFETY: Only the address part of our local pointer variable is overwritten
+        unsafe { ptr::write(&mut inner as *mut _ as *mut *mut u8, ptr) };
+        // Had the address not been the first word, the write above would have replaced the
+        // metadata instead and the address would still be the box's
+        debug_assert_eq!(inner as *mut u8, ptr, "unexpected fat pointer layout");
+
+        // SAFETY: The allocation fits the header plus the value at `offset`, and the value is
+        // moved bytewise, so the box allocation is freed without dropping it
+        unsafe {
+            ptr::write(ptr::addr_of_mut!((*inner).metadata), META::create());
+            #[cfg(feature = "track_location")]
+            ptr::write(ptr::addr_of_mut!((*inner).location), Location::caller());
+            ptr::copy_nonoverlapping(src as *const u8, ptr.add(offset), value_layout.size());
+            if value_layout.size() != 0 {
+                dealloc(src as *mut u8, value_layout);
+            }
+            Self::from_inner(NonNull::new_unchecked(inner))
         }
     }
 }
 
+impl<META, META2> FlexRc<META, META2, dyn Any + Send + Sync>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    /// Converts this into a handle to the concrete type if that is the type of the data, else
+    /// returns itself. No reference counts change either way
+    #[inline]
+    pub fn downcast<T: Any + Send + Sync>(self) -> Result<FlexRc<META, META2, T>, Self> {
+        if self.as_inner().data.is::<T>() {
+            // SAFETY: We just checked the type
+            Ok(unsafe { self.downcast_unchecked() })
+        } else {
+            Err(self)
+        }
+    }
+
+    /// # Safety
+    /// The data must be a `T`
+    #[inline]
+    pub(crate) unsafe fn downcast_unchecked<T: Any + Send + Sync>(self) -> FlexRc<META, META2, T> {
+        // Our reference moves to the new handle
+        let this = mem::ManuallyDrop::new(self);
+
+        // Dropping the vtable leaves a pointer to the same `repr(C)` inner: the header is the
+        // same, and the data offset (and allocation layout) only depend on the alignment of `T`
+        FlexRc::from_inner(this.ptr.cast())
+    }
+}
+
+impl<META, META2, T> From<T> for FlexRc<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn from(data: T) -> Self {
+        Self::new(data)
+    }
+}
+
+#[cfg(feature = "weak")]
+impl<META, META2, T> FlexRc<META, META2, T>
+where
+    META: WeakAlgorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    /// Creates a new weak handle to this allocation
+    #[inline]
+    pub fn downgrade(&self) -> FlexWeak<META, META2, T> {
+        self.as_inner().metadata.downgrade();
+        FlexWeak::from_inner(self.ptr)
+    }
+
+    /// Returns the number of weak handles to this allocation
+    #[inline]
+    pub fn weak_count(&self) -> usize {
+        self.as_inner().metadata.weak_count()
+    }
+}
+
 impl<META, META2, T> Deref for FlexRc<META, META2, T>
 where
     META: Algorithm<META, META2>,
@@ -315,6 +1450,21 @@ where
     }
 }
 
+// Reading a `MaybeUninit` is always valid, so uninitialized buffers can be inspected safely (use
+// `get_mut` to write into them while unique)
+impl<META, META2, T> Deref for FlexRc<META, META2, [mem::MaybeUninit<T>]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    type Target = [mem::MaybeUninit<T>];
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.as_inner().data
+    }
+}
+
 #[cfg(feature = "str_deref")]
 impl<META, META2> Deref for FlexRc<META, META2, [u8]>
 where
@@ -331,6 +1481,373 @@ where
     }
 }
 
+impl<META, META2> Deref for FlexRc<META, META2, str>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    type Target = str;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.as_inner().data
+    }
+}
+
+impl<'a, META, META2> From<&'a str> for FlexRc<META, META2, str>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn from(s: &'a str) -> Self {
+        // SAFETY: The bytes came from a `str`
+        unsafe { Self::from_bytes_unchecked(FlexRc::from_slice_priv(s.as_bytes())) }
+    }
+}
+
+impl<META, META2> From<Box<str>> for FlexRc<META, META2, str>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn from(s: Box<str>) -> Self {
+        s.into_string().into()
+    }
+}
+
+impl<META, META2> From<String> for FlexRc<META, META2, str>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn from(s: String) -> Self {
+        // SAFETY: The bytes came from a `String`
+        unsafe { Self::from_bytes_unchecked(FlexRc::from_vec_priv(s.into_bytes())) }
+    }
+}
+
+// Validates in place, so no copy is made
+impl<META, META2> TryFrom<FlexRc<META, META2, [u8]>> for FlexRc<META, META2, str>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    type Error = FlexRc<META, META2, [u8]>;
+
+    #[inline]
+    fn try_from(bytes: FlexRc<META, META2, [u8]>) -> Result<Self, Self::Error> {
+        match str::from_utf8(&bytes.as_inner().data) {
+            // SAFETY: We just validated the bytes
+            Ok(_) => Ok(unsafe { Self::from_bytes_unchecked(bytes) }),
+            Err(_) => Err(bytes),
+        }
+    }
+}
+
+// Validates before copying, like `from_utf8` does for the `[u8]` based strings
+impl<'a, META, META2> TryFrom<&'a [u8]> for FlexRc<META, META2, str>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    type Error = str::Utf8Error;
+
+    #[inline]
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        str::from_utf8(bytes).map(Self::from)
+    }
+}
+
+impl<META, META2> FromIterator<char> for FlexRc<META, META2, str>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        iter.into_iter().collect::<String>().into()
+    }
+}
+
+impl<'a, META, META2> FromIterator<&'a str> for FlexRc<META, META2, str>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        iter.into_iter().collect::<String>().into()
+    }
+}
+
+#[cfg(feature = "str_deref")]
+impl<META, META2> FromIterator<char> for FlexRc<META, META2, [u8]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        Self::from_str_ref(iter.into_iter().collect::<String>())
+    }
+}
+
+#[cfg(feature = "str_deref")]
+impl<'a, META, META2> FromIterator<&'a str> for FlexRc<META, META2, [u8]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        Self::from_str_ref(iter.into_iter().collect::<String>())
+    }
+}
+
+#[cfg(feature = "str_deref")]
+impl<'a, META, META2> TryFrom<&'a [u8]> for FlexRc<META, META2, [u8]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    type Error = str::Utf8Error;
+
+    #[inline]
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::from_utf8(bytes)
+    }
+}
+
+// An empty slice is also an empty (and valid) `str`, so this is fine with the str deref feature
+impl<META, META2, T> Default for FlexRc<META, META2, [T]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::from_vec_priv(Vec::new())
+    }
+}
+
+impl<META, META2> Default for FlexRc<META, META2, str>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::from("")
+    }
+}
+
+// Concatenation copies both operands into a single new allocation. The left hand handle is only
+// read, so it is fine for it to be shared
+impl<'a, META, META2> Add<&'a str> for FlexRc<META, META2, str>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: &'a str) -> Self::Output {
+        let bytes = FlexRc::<META, META2, [u8]>::concat_priv(
+            [self.as_bytes(), rhs.as_bytes()].into_iter(),
+            self.len() + rhs.len(),
+        );
+
+        // SAFETY: Both parts are valid UTF-8, and so is their concatenation
+        unsafe { Self::from_bytes_unchecked(bytes) }
+    }
+}
+
+impl<'a, META, META2> Add<&'a Self> for FlexRc<META, META2, str>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: &'a Self) -> Self::Output {
+        self + &**rhs
+    }
+}
+
+#[cfg(feature = "str_deref")]
+impl<'a, META, META2> Add<&'a str> for FlexRc<META, META2, [u8]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: &'a str) -> Self::Output {
+        let len = self.len() + rhs.len();
+        Self::concat_priv([self.as_bytes(), rhs.as_bytes()].into_iter(), len)
+    }
+}
+
+#[cfg(feature = "str_deref")]
+impl<'a, META, META2> Add<&'a Self> for FlexRc<META, META2, [u8]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: &'a Self) -> Self::Output {
+        Self::concat_rc(&[&self, rhs])
+    }
+}
+
+impl<META, META2, T> Borrow<T> for FlexRc<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    #[inline]
+    fn borrow(&self) -> &T {
+        &self.as_inner().data
+    }
+}
+
+// Lets shared strings be passed straight to filesystem APIs (`File::open` etc.)
+#[cfg(all(feature = "std", feature = "str_deref"))]
+impl<META, META2> AsRef<std::path::Path> for FlexRc<META, META2, [u8]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn as_ref(&self) -> &std::path::Path {
+        std::path::Path::new(&**self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<META, META2> AsRef<std::path::Path> for FlexRc<META, META2, str>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn as_ref(&self) -> &std::path::Path {
+        std::path::Path::new(&**self)
+    }
+}
+
+impl<META, META2, T> PartialEq for FlexRc<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: PartialEq + ?Sized,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_inner().data == other.as_inner().data
+    }
+}
+
+impl<META, META2, T> PartialEq<[T]> for FlexRc<META, META2, [T]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &[T]) -> bool {
+        self.as_inner().data == *other
+    }
+}
+
+impl<'a, META, META2, T> PartialEq<&'a [T]> for FlexRc<META, META2, [T]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &&'a [T]) -> bool {
+        self.as_inner().data == **other
+    }
+}
+
+impl<META, META2, T, const N: usize> PartialEq<[T; N]> for FlexRc<META, META2, [T]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &[T; N]) -> bool {
+        self.as_inner().data == *other.as_slice()
+    }
+}
+
+impl<'a, META, META2, T, const N: usize> PartialEq<&'a [T; N]> for FlexRc<META, META2, [T]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &&'a [T; N]) -> bool {
+        self.as_inner().data == *other.as_slice()
+    }
+}
+
+#[cfg(feature = "str_deref")]
+impl<META, META2> PartialEq<str> for FlexRc<META, META2, [u8]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_inner().data == *other.as_bytes()
+    }
+}
+
+#[cfg(feature = "str_deref")]
+impl<'a, META, META2> PartialEq<&'a str> for FlexRc<META, META2, [u8]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_inner().data == *other.as_bytes()
+    }
+}
+
+impl<META, META2, T> Eq for FlexRc<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: Eq + ?Sized,
+{
+}
+
+impl<META, META2, T> Hash for FlexRc<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: Hash + ?Sized,
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_inner().data.hash(state)
+    }
+}
+
 impl<META, META2, T> Clone for FlexRc<META, META2, T>
 where
     META: Algorithm<META, META2>,
@@ -340,6 +1857,8 @@ where
     #[inline(always)]
     fn clone(&self) -> Self {
         self.as_inner().metadata.clone();
+        #[cfg(feature = "metrics")]
+        metrics::record::<T>(MetricsEvent::Clone, self.allocation_size());
         Self::from_inner(self.ptr)
     }
 }
@@ -352,14 +1871,23 @@ where
 {
     #[inline(always)]
     fn drop(&mut self) {
+        #[cfg(feature = "metrics")]
+        metrics::record::<T>(MetricsEvent::Drop, self.allocation_size());
         let meta = &self.as_inner().metadata;
 
-        // If true, then ref count is zero
+        // If true, then strong ref count is zero
         if meta.drop() {
             // SAFETY: We own this memory, so guaranteed to exist while we have instance
             unsafe {
-                // Once back into a box, it will drop and deallocate normally
-                Box::from_raw(self.ptr.as_ptr());
+                // The data is destroyed with the last strong reference, but the storage must live on
+                // until the last weak reference is gone as well. The data destructor (including
+                // dropping any handles nested in the data) always completes before the storage is
+                // freed, so nested destructors may still safely run against this allocation
+                ptr::drop_in_place(&mut (*self.ptr.as_ptr()).data);
+
+                if (*self.ptr.as_ptr()).metadata.drop_weak() {
+                    dealloc_inner(self.ptr);
+                }
             }
         }
     }
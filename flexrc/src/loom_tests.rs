@@ -0,0 +1,209 @@
+use loom::cell::UnsafeCell;
+#[cfg(feature = "weak")]
+use loom::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "weak")]
+use loom::sync::Arc;
+use loom::thread;
+
+use crate::*;
+
+// `UnsafeCell` lets loom track accesses to the data, so it can tell whether the conversion
+// synchronized with the writes made through the handle dropped on the other thread
+struct Data(UnsafeCell<usize>);
+
+// SAFETY: The test only accesses the cell while it is the unique owner
+unsafe impl Sync for Data {}
+
+#[test]
+fn shared_into_local_here() {
+    loom::model(|| {
+        let shared = SharedRc::new(Data(UnsafeCell::new(0)));
+        let shared2 = shared.clone();
+
+        let handle = thread::spawn(move || {
+            // SAFETY: The main thread only touches the data once it is the unique owner
+            shared2.0.with_mut(|data| unsafe { *data = 1 });
+            drop(shared2);
+        });
+
+        match shared.try_into_local_here() {
+            Ok(local) => {
+                assert_eq!(local.as_inner().metadata.raw_count(), 1);
+
+                // The other handle is gone, so its write must be visible
+                local.0.with(|data| assert_eq!(unsafe { *data }, 1));
+                handle.join().unwrap();
+            }
+            Err(shared) => {
+                handle.join().unwrap();
+                assert!(shared.try_into_local_here().is_ok());
+            }
+        }
+    });
+}
+
+#[test]
+fn shared_into_local_here_after_nested_clone() {
+    loom::model(|| {
+        let shared = SharedRc::new(Data(UnsafeCell::new(0)));
+        let shared2 = shared.clone();
+
+        let handle = thread::spawn(move || {
+            // The write goes through a clone of a clone, which is dropped before the original
+            let shared3 = shared2.clone();
+            drop(shared2);
+            // SAFETY: The main thread only touches the data once it is the unique owner
+            shared3.0.with_mut(|data| unsafe { *data = 1 });
+        });
+
+        match shared.try_into_local_here() {
+            Ok(local) => {
+                // Every drop is a `Release` decrement and the uniqueness check an `Acquire` load,
+                // so seeing a count of one means the write is visible
+                local.0.with(|data| assert_eq!(unsafe { *data }, 1));
+                handle.join().unwrap();
+            }
+            Err(shared) => {
+                handle.join().unwrap();
+                assert!(shared.try_into_local_here().is_ok());
+            }
+        }
+    });
+}
+
+#[cfg(feature = "weak")]
+#[test]
+fn shared_into_local_here_with_weak() {
+    loom::model(|| {
+        let shared = SharedRc::new(Data(UnsafeCell::new(0)));
+        let weak = shared.downgrade();
+        let upgraded = Arc::new(AtomicBool::new(false));
+        let upgraded2 = upgraded.clone();
+
+        let handle = thread::spawn(move || {
+            if let Some(shared2) = weak.upgrade() {
+                // SAFETY: The main thread only touches the data once it is the unique owner
+                shared2.0.with_mut(|data| unsafe { *data = 1 });
+                upgraded2.store(true, Ordering::Relaxed);
+            }
+        });
+
+        match shared.try_into_local_here() {
+            Ok(local) => {
+                // The weak handle is only dropped after upgrading it, which can't fail while we
+                // still hold `shared`. So the upgrade happened, and since the weak handle is gone
+                // its write (and the flag set before it was dropped) must be visible
+                assert!(upgraded.load(Ordering::Relaxed));
+                local.0.with(|data| assert_eq!(unsafe { *data }, 1));
+                handle.join().unwrap();
+            }
+            Err(shared) => {
+                handle.join().unwrap();
+                assert!(shared.try_into_local_here().is_ok());
+            }
+        }
+    });
+}
+
+#[cfg(feature = "weak")]
+#[test]
+fn shared_strong_and_weak_drop_race() {
+    loom::model(|| {
+        let shared = SharedRc::new(Data(UnsafeCell::new(0)));
+        let weak = shared.downgrade();
+        let weak2 = weak.clone();
+
+        // The last strong and the last weak handle drop on different threads in either order, and
+        // only the last one of all may free the allocation
+        let handle = thread::spawn(move || {
+            // SAFETY: Only the thread holding the last strong handle touches the data
+            shared.0.with_mut(|data| unsafe { *data = 1 });
+            drop(shared);
+            drop(weak2);
+        });
+
+        if let Some(shared) = weak.upgrade() {
+            drop(shared);
+        }
+        drop(weak);
+        handle.join().unwrap();
+    });
+}
+
+#[test]
+fn shared_clone_drop_counts() {
+    loom::model(|| {
+        let shared = SharedRc::new(0usize);
+        let shared2 = shared.clone();
+        assert_eq!(shared.as_inner().metadata.raw_count(), 2);
+
+        let handle = thread::spawn(move || {
+            let shared3 = shared2.clone();
+            assert!(shared3.as_inner().metadata.raw_count() >= 2);
+            drop(shared2);
+            drop(shared3);
+        });
+
+        let shared4 = shared.clone();
+        handle.join().unwrap();
+        assert_eq!(shared.as_inner().metadata.raw_count(), 2);
+        drop(shared4);
+        assert_eq!(shared.as_inner().metadata.raw_count(), 1);
+    });
+}
+
+// The `track_threads` spinlock around conversions exhausts loom's branch limit, so only the
+// default and lock-free conversions are modeled
+#[cfg(any(not(feature = "track_threads"), feature = "track_threads_lockfree"))]
+#[test]
+fn hybrid_local_acquire_race() {
+    loom::model(|| {
+        let shared = SharedHybridRc::new(Data(UnsafeCell::new(0)));
+        let shared2 = shared.clone();
+        assert_eq!(shared.as_inner().metadata.raw_count(), 2);
+
+        // Only the thread owning the local side writes to the data, so if both threads ever got a
+        // local handle at the same time loom would report the writes as a data race
+        let handle = thread::spawn(move || {
+            if let Ok(local) = shared2.try_into_local() {
+                assert_eq!(local.as_inner().metadata.raw_count(), 1);
+                // SAFETY: See above
+                local.0.with_mut(|data| unsafe { *data += 1 });
+            }
+        });
+
+        if let Ok(local) = shared.try_into_local() {
+            assert_eq!(local.as_inner().metadata.raw_count(), 1);
+            // SAFETY: See above
+            local.0.with_mut(|data| unsafe { *data += 1 });
+        }
+        handle.join().unwrap();
+    });
+}
+
+#[cfg(feature = "track_threads_lockfree")]
+#[test]
+fn hybrid_owner_reacquires_during_race() {
+    loom::model(|| {
+        let shared = SharedHybridRc::new(Data(UnsafeCell::new(0)));
+        let shared2 = shared.clone();
+        let shared3 = shared.clone();
+
+        let handle = thread::spawn(move || {
+            if let Ok(local) = shared2.try_into_local() {
+                // SAFETY: Only the thread owning the local side writes to the data
+                local.0.with_mut(|data| unsafe { *data += 1 });
+            }
+        });
+
+        if let Ok(local) = shared.try_into_local() {
+            // The owning thread can always make more local handles, even while the other thread
+            // is trying to claim the local side
+            let local2 = shared3.try_into_local().ok().unwrap();
+            drop(local);
+            // SAFETY: Only the thread owning the local side writes to the data
+            local2.0.with_mut(|data| unsafe { *data += 1 });
+        }
+        handle.join().unwrap();
+    });
+}
@@ -0,0 +1,61 @@
+use core::any;
+use core::mem;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+// *** Metrics ***
+
+/// The kind of event reported to the metrics sink
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MetricsEvent {
+    /// A new allocation was made
+    New,
+    /// A handle was cloned
+    Clone,
+    /// A handle was dropped
+    Drop,
+    /// An allocation was freed
+    Dealloc,
+}
+
+/// A single event reported to the metrics sink
+#[derive(Clone, Copy, Debug)]
+pub struct MetricsRecord {
+    pub event: MetricsEvent,
+    /// Name of the data type (as given by `core::any::type_name`)
+    pub type_name: &'static str,
+    /// Size in bytes of the whole allocation (metadata included)
+    pub size: usize,
+}
+
+/// Callback invoked for every metrics event. It may be called from any thread, and must not
+/// create, clone or drop handles itself
+pub type MetricsSink = fn(&MetricsRecord);
+
+static SINK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Sets (or with `None`, clears) the global metrics sink. Events are only reported while a sink
+/// is set
+#[inline]
+pub fn set_metrics_sink(sink: Option<MetricsSink>) {
+    let sink = match sink {
+        Some(sink) => sink as *mut (),
+        None => ptr::null_mut(),
+    };
+    SINK.store(sink, Ordering::Release);
+}
+
+#[inline]
+pub(crate) fn record<T: ?Sized>(event: MetricsEvent, size: usize) {
+    let sink = SINK.load(Ordering::Acquire);
+
+    if !sink.is_null() {
+        // SAFETY: The only non-null values ever stored are `MetricsSink` function pointers
+        let sink = unsafe { mem::transmute::<*mut (), MetricsSink>(sink) };
+        sink(&MetricsRecord {
+            event,
+            type_name: any::type_name::<T>(),
+            size,
+        });
+    }
+}
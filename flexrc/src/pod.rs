@@ -0,0 +1,63 @@
+#[cfg(not(feature = "str_deref"))]
+use core::mem;
+#[cfg(not(feature = "str_deref"))]
+use core::ptr::NonNull;
+
+use bytemuck::Pod;
+
+#[cfg(not(feature = "str_deref"))]
+use crate::FlexRcInner;
+use crate::{Algorithm, FlexRc};
+
+impl<META, META2, T> FlexRc<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: Pod,
+{
+    /// Views the value as its raw bytes (`size_of::<T>()` of them), for hashing or writing it out
+    /// without a copy
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        bytemuck::bytes_of(&self.as_inner().data)
+    }
+}
+
+// Not available with the `str_deref` feature since the result could be `[u8]` holding invalid UTF-8
+#[cfg(not(feature = "str_deref"))]
+impl<META, META2, T> FlexRc<META, META2, [T]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: Pod,
+{
+    /// Reinterprets the elements as `U` in place, without copying and keeping the length. Both
+    /// types must have the same size and alignment (checked at compile time), so the allocation
+    /// layout is unchanged. Any other handles to the allocation keep seeing the same bytes as `T`,
+    /// which is fine since both are `Pod`. Like `from_slice`, this is not available with the
+    /// `str_deref` feature
+    #[inline]
+    pub fn cast_elements<U: Pod>(self) -> FlexRc<META, META2, [U]> {
+        const {
+            assert!(
+                mem::size_of::<T>() == mem::size_of::<U>(),
+                "element sizes differ"
+            );
+            assert!(
+                mem::align_of::<T>() == mem::align_of::<U>(),
+                "element alignments differ"
+            );
+        }
+
+        // Avoid drop to ensure no ref count decrement
+        let this = mem::ManuallyDrop::new(self);
+
+        // SAFETY: Same size and alignment, so the header, the data offset and the slice length (in
+        // elements) all stay valid, and any bit pattern is a valid `U`
+        unsafe {
+            FlexRc::from_inner(NonNull::new_unchecked(
+                this.ptr.as_ptr() as *mut FlexRcInner<META, META2, [U]>
+            ))
+        }
+    }
+}
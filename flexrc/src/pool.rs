@@ -0,0 +1,117 @@
+use alloc::alloc::{alloc, dealloc, handle_alloc_error};
+use alloc::vec::Vec;
+use core::alloc::Layout;
+use core::cell::RefCell;
+use core::ptr::{self, NonNull};
+
+use crate::{Algorithm, FlexRc, FlexRcInner};
+
+// Most freed allocations kept per layout per thread. Anything beyond this goes back to the
+// allocator so a burst of drops can't pin memory forever
+const MAX_POOLED: usize = 64;
+
+thread_local! { static POOL: RefCell<Pool> = RefCell::new(Pool::default()) }
+
+// *** Pool ***
+
+// Free lists of allocations keyed by layout. Allocations all come from the global allocator and
+// carry no type, so any allocation with a matching layout can be reused for any type
+#[derive(Default)]
+struct Pool {
+    free_lists: Vec<(Layout, Vec<NonNull<u8>>)>,
+}
+
+impl Pool {
+    #[inline]
+    fn free_list(&mut self, layout: Layout) -> Option<&mut Vec<NonNull<u8>>> {
+        self.free_lists
+            .iter_mut()
+            .find(|(l, _)| *l == layout)
+            .map(|(_, list)| list)
+    }
+}
+
+impl Drop for Pool {
+    fn drop(&mut self) {
+        for (layout, list) in self.free_lists.drain(..) {
+            for ptr in list {
+                // SAFETY: Every pooled pointer came from the global allocator with this layout
+                unsafe { dealloc(ptr.as_ptr(), layout) };
+            }
+        }
+    }
+}
+
+// Takes a pooled allocation for `layout` if one is available, and marks the layout as pooled so
+// allocations freed on this thread are recycled from now on
+#[inline]
+fn take(layout: Layout) -> Option<NonNull<u8>> {
+    POOL.try_with(|pool| {
+        let mut pool = pool.borrow_mut();
+        match pool.free_list(layout) {
+            Some(list) => list.pop(),
+            None => {
+                pool.free_lists.push((layout, Vec::new()));
+                None
+            }
+        }
+    })
+    .ok()
+    .flatten()
+}
+
+/// Returns `ptr` to this thread's pool if its layout is pooled here and the pool isn't full, and
+/// returns false if the caller must free it instead
+///
+/// # Safety
+/// `ptr` must come from the global allocator with `layout` and nothing may reference it anymore
+#[inline]
+pub(crate) unsafe fn give(ptr: NonNull<u8>, layout: Layout) -> bool {
+    POOL.try_with(|pool| match pool.borrow_mut().free_list(layout) {
+        Some(list) if list.len() < MAX_POOLED => {
+            list.push(ptr);
+            true
+        }
+        _ => false,
+    })
+    // The pool is already gone while the thread shuts down
+    .unwrap_or(false)
+}
+
+impl<META, META2, T> FlexRc<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    /// Same as `new`, but reuses an allocation from this thread's pool when one is available.
+    /// From the first call on, allocations of the same layout freed on this thread (from any
+    /// handle, pooled or not) are kept for reuse instead of being returned to the allocator. This
+    /// suits workloads that churn many short-lived handles of the same size
+    #[cfg_attr(feature = "track_location", track_caller)]
+    #[inline]
+    pub fn new_pooled(data: T) -> Self {
+        let layout = Layout::new::<FlexRcInner<META, META2, T>>();
+
+        let ptr = match take(layout) {
+            Some(ptr) => ptr.as_ptr(),
+            // SAFETY: The layout has a non-zero size since it always includes the metadata
+            None => unsafe { alloc(layout) },
+        };
+        let ptr = match NonNull::new(ptr as *mut FlexRcInner<META, META2, T>) {
+            Some(ptr) => ptr,
+            None => handle_alloc_error(layout),
+        };
+
+        #[cfg(feature = "leak_check")]
+        crate::leak_check::alloc_created();
+        #[cfg(feature = "metrics")]
+        crate::metrics::record::<T>(crate::MetricsEvent::New, layout.size());
+
+        // SAFETY: The allocation fits this layout and holds nothing, so we write a fresh inner
+        // (including newly created counters) without dropping whatever was there before
+        unsafe {
+            ptr::write(ptr.as_ptr(), FlexRcInner::with_meta(data, META::create()));
+        }
+        Self::from_inner(ptr)
+    }
+}
@@ -0,0 +1,74 @@
+use core::mem;
+use core::ops::Deref;
+use core::ptr::NonNull;
+
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::{Algorithm, FlexRc, FlexRcInner, LocalRc, SharedRc};
+
+// *** Secret ***
+
+/// Wraps data that must be wiped from memory once it is no longer used. The data is zeroed when
+/// the wrapper drops, which for a handle happens when the last strong reference drops. Weak
+/// references only keep the (already zeroed) allocation alive, never the data itself
+#[repr(transparent)]
+pub struct Secret<T: Zeroize + ?Sized>(T);
+
+/// A local handle to secret bytes which are zeroed when the last strong reference drops
+pub type LocalSecretRc = LocalRc<Secret<[u8]>>;
+
+/// A shared handle to secret bytes which are zeroed when the last strong reference drops
+pub type SharedSecretRc = SharedRc<Secret<[u8]>>;
+
+impl<T: Zeroize> Secret<T> {
+    #[inline]
+    pub fn new(data: T) -> Self {
+        Self(data)
+    }
+}
+
+impl<T: Zeroize + ?Sized> Deref for Secret<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: Zeroize + ?Sized> Drop for Secret<T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize + ?Sized> ZeroizeOnDrop for Secret<T> {}
+
+impl<META, META2> FlexRc<META, META2, Secret<[u8]>>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    /// Copies `data` into a new allocation that is zeroed when the last strong reference drops. The
+    /// caller remains responsible for wiping `data` itself
+    #[inline]
+    pub fn from_secret_slice(data: &[u8]) -> Self {
+        // Avoid drop to ensure no ref count decrement
+        let rc = mem::ManuallyDrop::new(FlexRc::<META, META2, [u8]>::from_slice_priv(data));
+
+        // SAFETY: `Secret` is `repr(transparent)` so the inner layout is unchanged and the fat
+        // pointer length carries over as is
+        unsafe {
+            Self::from_inner(NonNull::new_unchecked(
+                rc.ptr.as_ptr() as *mut FlexRcInner<META, META2, Secret<[u8]>>
+            ))
+        }
+    }
+
+    /// Returns the secret bytes
+    #[inline]
+    pub fn expose(&self) -> &[u8] {
+        &self.as_inner().data.0
+    }
+}
@@ -0,0 +1,170 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::mem;
+use core::ptr;
+
+use crate::{dealloc_inner, Algorithm, FlexRc, LocalRc, SharedRc};
+
+// *** IntoShared ***
+
+/// Deeply converts a value holding local handles into one holding shared handles, so a whole tree
+/// of `LocalRc` (for example `LocalRc<[LocalRc<T>]>`) can be handed off to other threads in one call.
+///
+/// Since the data type changes along with the handle type, every local handle is converted into a
+/// new allocation. Data that is uniquely owned is moved into it, otherwise it is cloned
+pub trait IntoShared {
+    type Shared;
+
+    fn into_shared(self) -> Self::Shared;
+}
+
+impl<META, META2, T> FlexRc<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    // Moves the data out if this is the only reference to it, else clones it
+    #[inline]
+    fn into_data_or_clone(self) -> T
+    where
+        T: Clone,
+    {
+        let this = mem::ManuallyDrop::new(self);
+
+        if this.is_unique() {
+            // SAFETY: We are the only reference (strong or weak), so we can move the data out and
+            // free the allocation without dropping the data a second time
+            unsafe {
+                let data = ptr::read(&this.as_inner().data);
+                dealloc_inner(this.ptr);
+                data
+            }
+        } else {
+            let data = this.as_inner().data.clone();
+            drop(mem::ManuallyDrop::into_inner(this));
+            data
+        }
+    }
+}
+
+impl<META, META2, T> FlexRc<META, META2, [T]>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    // Moves the elements out if this is the only reference to them, else clones them
+    #[inline]
+    fn into_vec_or_clone(self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let this = mem::ManuallyDrop::new(self);
+        let data = &this.as_inner().data;
+
+        if this.is_unique() {
+            let mut v = Vec::with_capacity(data.len());
+
+            // SAFETY: We are the only reference (strong or weak), so we can move the elements out
+            // and free the allocation without dropping them a second time
+            unsafe {
+                ptr::copy_nonoverlapping(data.as_ptr(), v.as_mut_ptr(), data.len());
+                v.set_len(data.len());
+                dealloc_inner(this.ptr);
+            }
+            v
+        } else {
+            let v = data.to_vec();
+            drop(mem::ManuallyDrop::into_inner(this));
+            v
+        }
+    }
+}
+
+impl<T> IntoShared for LocalRc<T>
+where
+    T: IntoShared + Clone,
+{
+    type Shared = SharedRc<T::Shared>;
+
+    #[inline]
+    fn into_shared(self) -> Self::Shared {
+        SharedRc::new(self.into_data_or_clone().into_shared())
+    }
+}
+
+impl<T> IntoShared for LocalRc<[T]>
+where
+    T: IntoShared + Clone,
+{
+    type Shared = SharedRc<[T::Shared]>;
+
+    #[inline]
+    fn into_shared(self) -> Self::Shared {
+        SharedRc::from_vec_priv(self.into_vec_or_clone().into_shared())
+    }
+}
+
+// Already shared, so nothing below needs converting
+impl<T: ?Sized> IntoShared for SharedRc<T> {
+    type Shared = Self;
+
+    #[inline(always)]
+    fn into_shared(self) -> Self::Shared {
+        self
+    }
+}
+
+impl<T: IntoShared> IntoShared for Vec<T> {
+    type Shared = Vec<T::Shared>;
+
+    #[inline]
+    fn into_shared(self) -> Self::Shared {
+        self.into_iter().map(IntoShared::into_shared).collect()
+    }
+}
+
+impl<T: IntoShared> IntoShared for Option<T> {
+    type Shared = Option<T::Shared>;
+
+    #[inline]
+    fn into_shared(self) -> Self::Shared {
+        self.map(IntoShared::into_shared)
+    }
+}
+
+// Leaf types can't hold handles, so they are shared as is
+macro_rules! leaf_into_shared {
+    ($($ty:ty),*) => {
+        $(
+            impl IntoShared for $ty {
+                type Shared = Self;
+
+                #[inline(always)]
+                fn into_shared(self) -> Self::Shared {
+                    self
+                }
+            }
+        )*
+    };
+}
+
+leaf_into_shared!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    String
+);
@@ -1 +1,1905 @@
+use alloc::string::String;
+use core::mem;
 
+use crate::*;
+
+#[test]
+fn regular_convert_unique() {
+    let local = LocalRc::new(String::from("test"));
+    let shared = local.try_into_other().ok().unwrap();
+    assert_eq!(shared.strong_count(), 1);
+    assert_eq!(*shared, "test");
+
+    let local = shared.try_into_other().ok().unwrap();
+    assert_eq!(local.strong_count(), 1);
+    assert_eq!(*local, "test");
+}
+
+#[test]
+fn hybrid_convert_unique_shared_to_local() {
+    let shared = SharedHybridRc::new(String::from("test"));
+    let local = shared.try_into_other().ok().unwrap();
+    assert_eq!(local.strong_count(), 1);
+    assert_eq!(*local, "test");
+
+    let shared = local.try_into_other().ok().unwrap();
+    assert_eq!(shared.strong_count(), 1);
+    assert_eq!(*shared, "test");
+}
+
+#[test]
+fn unique_conversion_keeps_allocation() {
+    let local = LocalRc::<[u8]>::from_str_ref("x".repeat(1024));
+    let ptr = local.as_bytes().as_ptr();
+
+    let identity = local.identity();
+    assert_eq!(identity, ptr as usize);
+
+    let shared = local.try_into_other().ok().unwrap();
+    assert_eq!(shared.as_bytes().as_ptr(), ptr);
+    assert_eq!(shared.identity(), identity);
+
+    let hybrid = LocalHybridRc::<[u8]>::from_str_ref("x".repeat(1024));
+    let ptr = hybrid.as_bytes().as_ptr();
+    assert_eq!(
+        hybrid.try_into_other().ok().unwrap().as_bytes().as_ptr(),
+        ptr
+    );
+}
+
+#[test]
+fn convert_via_try_from() {
+    let local = LocalRc::new(1u8);
+    let local2 = local.clone();
+    let local = SharedRc::<u8>::try_from(local).err().unwrap();
+    drop(local2);
+    let shared: SharedRc<u8> = local.try_into().ok().unwrap();
+    let local = LocalRc::<u8>::try_from(shared).ok().unwrap();
+    assert_eq!(*local, 1);
+
+    let shared = SharedHybridRc::<u8>::try_from(LocalHybridRc::new(2u8))
+        .ok()
+        .unwrap();
+    let local: LocalHybridRc<u8> = shared.try_into().ok().unwrap();
+    assert_eq!(*local, 2);
+}
+
+#[test]
+fn shared_clone_drop_convert() {
+    let shared = SharedRc::new(String::from("test"));
+    let shared2 = shared.clone();
+    let shared = shared.try_into_local_here().err().unwrap();
+
+    drop(shared2);
+    let local = shared.try_into_local_here().ok().unwrap();
+    assert_eq!(local.strong_count(), 1);
+    assert_eq!(*local, "test");
+}
+
+#[test]
+fn seq_cst_orderings() {
+    let shared = SeqCstSharedRc::new(String::from("test"));
+    let shared2 = shared.clone();
+    assert_eq!(shared.strong_count(), 2);
+    let shared = shared.try_into_other().err().unwrap();
+    drop(shared2);
+
+    let local: SeqCstLocalRc<String> = shared.try_into_other().ok().unwrap();
+    assert_eq!(*local, "test");
+    let shared = local.try_into_other().ok().unwrap();
+    assert!(shared.is_unique());
+}
+
+#[test]
+fn hybrid_presence_queries() {
+    let local = LocalHybridRc::new(1u8);
+    assert!(!local.is_shared_elsewhere());
+
+    let shared = local.to_other();
+    assert!(local.is_shared_elsewhere());
+    assert!(shared.is_local_present());
+
+    drop(local);
+    assert!(!shared.is_local_present());
+}
+
+#[test]
+fn hybrid_convert_non_unique_shared_to_local() {
+    let shared = SharedHybridRc::new(String::from("test"));
+    let shared2 = shared.clone();
+    let local = shared.try_into_other().ok().unwrap();
+    assert_eq!(local.strong_count(), 2);
+    assert_eq!(shared2.strong_count(), 2);
+
+    // The local slot is taken, so without thread tracking a second conversion must fail
+    #[cfg(not(feature = "track_threads"))]
+    {
+        let shared3 = shared2.clone();
+        assert!(shared3.try_into_other().is_err());
+    }
+
+    drop(local);
+    assert_eq!(shared2.strong_count(), 1);
+    assert_eq!(*shared2, "test");
+}
+
+#[cfg(feature = "weak")]
+#[test]
+fn weak_blocks_conversion() {
+    let local = LocalRc::new(String::from("test"));
+    let weak = local.downgrade();
+
+    // The weak handle ties the allocation to the local type
+    let local = local.try_into_other().err().unwrap();
+    assert_eq!(weak.upgrade().as_deref(), Some(&String::from("test")));
+
+    drop(weak);
+    let shared = local.try_into_other().ok().unwrap();
+    assert_eq!(*shared, "test");
+
+    let weak = shared.downgrade();
+    let shared = shared.try_into_other().err().unwrap();
+    drop(weak);
+    let local = shared.try_into_other().ok().unwrap();
+    assert_eq!(*local, "test");
+}
+
+#[cfg(feature = "weak")]
+#[test]
+fn weak_blocks_get_mut() {
+    let mut local = LocalRc::new(1u32);
+    let weak = local.downgrade();
+    assert!(local.get_mut().is_none());
+    drop(weak);
+    *local.get_mut().unwrap() += 1;
+
+    let mut shared = SharedRc::new(1u32);
+    let weak = shared.downgrade();
+    assert!(shared.get_mut().is_none());
+    drop(weak);
+    *shared.get_mut().unwrap() += 1;
+    assert_eq!(*shared, *local);
+}
+
+#[cfg(feature = "weak")]
+#[test]
+fn weak_clone() {
+    let shared = SharedRc::new(String::from("test"));
+    let weak = shared.downgrade();
+    let weak2 = weak.clone();
+    assert_eq!(shared.weak_count(), 2);
+    assert!(weak.ptr_eq(&weak2));
+
+    // The data goes with the last strong handle, the allocation with the last weak one
+    drop(shared);
+    assert!(weak2.upgrade().is_none());
+    drop(weak);
+    assert_eq!(weak2.strong_count(), 0);
+    drop(weak2);
+
+    let local = LocalRc::new(1u8);
+    let weak = local.downgrade().clone();
+    assert_eq!(local.weak_count(), 1);
+    drop(weak.clone());
+    assert_eq!(weak.upgrade().as_deref(), Some(&1));
+
+    let dangling = SharedWeak::<u8>::new();
+    assert!(dangling.clone().upgrade().is_none());
+}
+
+#[cfg(feature = "weak")]
+#[test]
+fn weak_after_conversion() {
+    let local = LocalRc::new(String::from("test"));
+    let shared = local.try_into_other().ok().unwrap();
+
+    let weak = shared.downgrade();
+    assert_eq!(shared.weak_count(), 1);
+    assert_eq!(weak.strong_count(), 1);
+
+    let shared2 = weak.upgrade().unwrap();
+    assert_eq!(shared2.strong_count(), 2);
+    assert_eq!(*shared2, "test");
+
+    drop(shared);
+    drop(shared2);
+    assert!(weak.upgrade().is_none());
+    assert_eq!(weak.strong_count(), 0);
+    assert_eq!(weak.weak_count(), 0);
+}
+
+#[test]
+fn unique_guard_as_mut() {
+    fn push_str(mut s: impl AsMut<String>, suffix: &str) {
+        s.as_mut().push_str(suffix);
+    }
+
+    let mut local = LocalRc::new(String::from("test"));
+    push_str(local.as_unique_mut().unwrap(), "ing");
+    assert_eq!(*local, "testing");
+
+    let local2 = local.clone();
+    assert!(local.as_unique_mut().is_none());
+    drop(local2);
+    assert!(local.as_unique_mut().is_some());
+}
+
+#[test]
+fn family_conversion() {
+    let shared = SharedRc::new(String::from("test"));
+    let hybrid = shared.to_hybrid();
+    assert_eq!(*hybrid, "test");
+    assert_eq!(shared.strong_count(), 1);
+
+    let regular = hybrid.to_regular();
+    assert_eq!(*regular, "test");
+    assert_eq!(hybrid.strong_count(), 1);
+}
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn secret_zeroed_on_drop() {
+    let secret = SharedSecretRc::from_secret_slice(b"hunter2");
+    let secret2 = secret.clone();
+    drop(secret);
+    assert_eq!(secret2.expose(), b"hunter2");
+
+    // Drop the data the same way the last handle would, but inspect the bytes before freeing
+    let secret2 = core::mem::ManuallyDrop::new(secret2);
+    let inner = secret2.ptr.as_ptr();
+    // SAFETY: `secret2` is never used or dropped again and the allocation is freed exactly once
+    unsafe {
+        core::ptr::drop_in_place(core::ptr::addr_of_mut!((*inner).data));
+        let data = core::ptr::addr_of!((*inner).data) as *const u8;
+        assert_eq!(core::slice::from_raw_parts(data, 7), [0; 7]);
+        dealloc_inner(secret2.ptr);
+    }
+}
+
+#[cfg(all(feature = "std", not(feature = "str_deref")))]
+#[test]
+fn from_reader() {
+    let mut reader: &[u8] = b"test data";
+    let rc = SharedRc::<[u8]>::from_reader(&mut reader, 4).unwrap();
+    assert_eq!(rc.as_bytes(), b"test");
+    assert_eq!(reader, b" data");
+
+    let err = SharedRc::<[u8]>::from_reader(&mut reader, 10)
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn ord_by_total_cmp() {
+    let mut values: alloc::vec::Vec<_> = [1.5, f64::NAN, -0.0, 0.0, f64::NEG_INFINITY]
+        .into_iter()
+        .map(|f| OrdByTotalCmp(SharedRc::new(f)))
+        .collect();
+    values.sort();
+
+    let sorted: alloc::vec::Vec<u64> = values.iter().map(|v| v.to_bits()).collect();
+    let expected: alloc::vec::Vec<u64> = [f64::NEG_INFINITY, -0.0, 0.0, 1.5, f64::NAN]
+        .iter()
+        .map(|f| f.to_bits())
+        .collect();
+    assert_eq!(sorted, expected);
+    assert!(OrdByTotalCmp(SharedRc::new(f64::NAN)) == OrdByTotalCmp(SharedRc::new(f64::NAN)));
+}
+
+#[test]
+fn can_convert() {
+    let local = LocalRc::new(String::from("test"));
+    assert!(local.can_convert());
+    let local2 = local.clone();
+    assert!(!local.can_convert());
+    drop(local2);
+    assert!(local.try_into_other().is_ok());
+
+    let shared = SharedHybridRc::new(String::from("test"));
+    assert!(shared.can_convert());
+    let shared2 = shared.clone();
+    let local = shared.try_into_other().ok().unwrap();
+    assert!(local.can_convert());
+
+    // The local slot is taken, so without thread tracking a conversion would fail
+    #[cfg(not(feature = "track_threads"))]
+    assert!(!shared2.can_convert());
+    #[cfg(feature = "track_threads")]
+    assert!(shared2.can_convert());
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn ffi_round_trip() {
+    let rc = SharedRc::<[u8]>::from_str_ref("ffi");
+    let handle = rc.clone().into_ffi();
+
+    unsafe {
+        let handle2 = flexrc_clone(handle);
+        assert_eq!(rc.strong_count(), 3);
+        assert_eq!(flexrc_len(handle2), 3);
+        assert_eq!(
+            core::slice::from_raw_parts(flexrc_data_ptr(handle2), 3),
+            b"ffi"
+        );
+
+        flexrc_drop(handle2);
+        assert_eq!(rc.strong_count(), 2);
+        assert_eq!(SharedRc::from_ffi(handle).as_bytes(), b"ffi");
+    }
+    assert_eq!(rc.strong_count(), 1);
+}
+
+#[test]
+fn slice_default() {
+    #[derive(Default)]
+    struct Config {
+        name: SharedRc<[u8]>,
+        values: LocalRc<[u64]>,
+    }
+
+    let config = Config::default();
+    assert_eq!(config.name.as_str(), Ok(""));
+    assert!(config.values.is_empty());
+    assert_eq!(LocalRc::<[u64]>::from_vec_priv(alloc::vec![1, 2]).len(), 2);
+}
+
+#[test]
+fn str_dst() {
+    let local: LocalRc<str> = "hello".into();
+    let shared: SharedRc<str> = String::from("hello").into();
+    assert_eq!(&*local, "hello");
+    assert_eq!(local.len(), 5);
+    assert_eq!(LocalRc::<str>::from("h\u{e9}llo").byte_len(), 6);
+    assert_eq!(LocalRc::<str>::from("h\u{e9}llo").char_len(), 5);
+    assert!(local.try_into_other().ok().unwrap() == shared);
+
+    // Round trips through bytes reuse the allocation
+    let bytes = shared.clone().into_bytes();
+    assert_eq!(bytes.as_bytes(), b"hello");
+    let shared2 = SharedRc::<str>::try_from(bytes).ok().unwrap();
+    assert!(SharedRc::ptr_eq(&shared, &shared2));
+    assert_eq!(shared.strong_count(), 2);
+
+    #[cfg(not(feature = "str_deref"))]
+    assert!(SharedRc::<str>::try_from(SharedRc::<[u8]>::from_slice(b"\xff")).is_err());
+}
+
+#[test]
+fn str_dst_constructors() {
+    let s = LocalRc::<str>::try_from_str("try").ok().unwrap();
+    assert_eq!(&*s, "try");
+
+    let s = SharedRc::<str>::try_from(&b"valid"[..]).ok().unwrap();
+    assert_eq!(&*s, "valid");
+    assert!(SharedRc::<str>::try_from(&b"\xff"[..]).is_err());
+
+    let s: LocalRc<str> = ['a', '\u{e9}', 'b'].into_iter().collect();
+    assert_eq!(&*s, "a\u{e9}b");
+    let s: SharedRc<str> = ["ab", "", "cd"].into_iter().collect();
+    assert_eq!(&*s, "abcd");
+    assert_eq!(&*LocalRc::<str>::default(), "");
+}
+
+#[test]
+fn bytes_concat() {
+    let head = SharedRc::<[u8]>::from_str_ref("GET / ");
+    let tail = SharedRc::<[u8]>::from_str_ref("HTTP/1.1");
+    let line = SharedRc::concat_rc(&[&head, &tail]);
+    assert_eq!(line.as_str(), Ok("GET / HTTP/1.1"));
+    assert_eq!(SharedRc::concat_rc(&[]).as_bytes().len(), 0);
+
+    #[cfg(not(feature = "str_deref"))]
+    {
+        let joined = LocalRc::<[u8]>::concat(&[b"ab", b"", b"\xff"]);
+        assert_eq!(joined.as_bytes(), b"ab\xff");
+        assert_eq!(LocalRc::<[u8]>::concat(&[]).as_bytes().len(), 0);
+    }
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn metrics_events() {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Tracked;
+    static EVENTS: [AtomicUsize; 4] = [const { AtomicUsize::new(0) }; 4];
+
+    // Other tests may run concurrently, so only count events for our own type
+    fn sink(record: &MetricsRecord) {
+        if record.type_name.ends_with("Tracked") {
+            EVENTS[record.event as usize].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    set_metrics_sink(Some(sink));
+    let rc = SharedRc::new(Tracked);
+    let rc2 = rc.clone();
+    drop(rc);
+    drop(rc2);
+    set_metrics_sink(None);
+
+    let events: Vec<_> = EVENTS.iter().map(|e| e.load(Ordering::Relaxed)).collect();
+    assert_eq!(events, [1, 1, 2, 1]);
+}
+
+#[test]
+fn str_builder() {
+    use core::fmt::Write;
+
+    let mut builder = SharedStrBuilder::with_capacity(32);
+    let capacity = builder.capacity();
+    assert!(capacity >= 32);
+
+    builder.push_str("id=");
+    write!(builder, "{}", 42).unwrap();
+    builder.push(';');
+    assert_eq!(builder.capacity(), capacity);
+
+    let s = builder.finish();
+    assert_eq!(&*s, "id=42;");
+    assert!(LocalStrBuilder::new().finish().is_empty());
+}
+
+#[test]
+fn str_add() {
+    let greeting = SharedRc::<str>::from("hello");
+    let name = SharedRc::<str>::from(" world");
+    assert_eq!(&*(greeting.clone() + &name), "hello world");
+    assert_eq!(&*(greeting.clone() + ""), "hello");
+    assert_eq!(&*(SharedRc::<str>::from("") + &greeting), "hello");
+
+    #[cfg(feature = "str_deref")]
+    {
+        let s = SharedStr::from_str_ref("a") + "b";
+        assert_eq!(&*(s + &SharedStr::from_str_ref("c")), "abc");
+    }
+}
+
+#[test]
+fn str_from_utf8_lossy() {
+    let valid = SharedRc::<[u8]>::from_utf8_lossy(b"hello");
+    assert_eq!(valid.as_str(), Ok("hello"));
+
+    let invalid = LocalRc::<[u8]>::from_utf8_lossy(b"ab\xffcd");
+    assert_eq!(invalid.as_str(), Ok("ab\u{FFFD}cd"));
+}
+
+#[test]
+fn bytes_split_off() {
+    let rc = SharedRc::<[u8]>::from_str_ref("header:body");
+    let mut first = SharedBytes::from(rc.clone());
+    let second = first.split_off(7);
+    assert_eq!(&*first, b"header:");
+    assert_eq!(&*second, b"body");
+    assert_eq!(rc.strong_count(), 3);
+    drop(rc);
+
+    // Either half keeps the allocation alive on its own
+    drop(first);
+    assert_eq!(&*second, b"body");
+    let mut second = second;
+    let empty = second.split_off(4);
+    assert!(empty.is_empty());
+    drop(second);
+    assert_eq!(empty.len(), 0);
+}
+
+#[test]
+#[should_panic]
+fn bytes_split_off_out_of_bounds() {
+    let mut bytes = SharedBytes::from(SharedRc::<[u8]>::from_str_ref("test"));
+    bytes.split_off(5);
+}
+
+#[test]
+fn bytes_chunks_and_windows() {
+    let rc = SharedRc::<[u8]>::from_str_ref("abcdefg");
+    let mut bytes = SharedBytes::from(rc.clone());
+    let _ = bytes.split_off(6);
+
+    let chunks: Vec<_> = bytes.chunks(4).collect();
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(&*chunks[0], b"abcd");
+    assert_eq!(&*chunks[1], b"ef");
+    assert_eq!(rc.strong_count(), 4);
+    drop(chunks);
+    assert_eq!(rc.strong_count(), 2);
+
+    let windows = bytes.windows(5);
+    assert_eq!(windows.len(), 2);
+    let windows: Vec<_> = windows.collect();
+    assert_eq!(&*windows[0], b"abcde");
+    assert_eq!(&*windows[1], b"bcdef");
+    assert_eq!(bytes.windows(7).count(), 0);
+
+    // Views outlive both the original handle and the view they came from
+    drop(rc);
+    drop(bytes);
+    assert_eq!(&*windows[1], b"bcdef");
+}
+
+#[test]
+fn slice_data_offset() {
+    use core::alloc::Layout;
+
+    fn check<T>() {
+        let rc = LocalRc::<[T]>::new_slice_uninit(3);
+        let header = Layout::new::<FlexRcInner<LocalMeta, SharedMeta, ()>>();
+        let (layout, offset) = header.extend(Layout::array::<T>(3).unwrap()).unwrap();
+
+        // The elements start where `slice_layout` expects and the allocation is exactly its size
+        assert_eq!(
+            rc.identity() - rc.ptr.as_ptr() as *const u8 as usize,
+            offset
+        );
+        assert_eq!(
+            slice_layout::<LocalMeta, SharedMeta, T>(3).unwrap(),
+            layout.pad_to_align()
+        );
+        assert_eq!(rc.allocation_size(), layout.pad_to_align().size());
+    }
+
+    check::<()>();
+    check::<u8>();
+    check::<u64>();
+    check::<u128>();
+    check::<[u8; 3]>();
+}
+
+#[test]
+fn uninit_slice_access() {
+    let mut buf = LocalRc::<[u64]>::new_slice_uninit(3);
+    assert_eq!(buf.len(), 3);
+    for (i, elem) in buf.get_mut().unwrap().iter_mut().enumerate() {
+        elem.write(i as u64 * 2);
+    }
+
+    let buf2 = buf.clone();
+    assert!(buf.get_mut().is_none());
+    drop(buf2);
+
+    // SAFETY: Every element was written above
+    let buf = unsafe { buf.assume_init() };
+    assert!(buf == [0, 2, 4]);
+}
+
+#[test]
+fn allocation_size() {
+    // The header only holds the metadata, plus the allocation site with `track_location`
+    #[cfg(not(feature = "track_location"))]
+    const EXTRA: usize = 0;
+    #[cfg(feature = "track_location")]
+    const EXTRA: usize = mem::size_of::<&core::panic::Location>();
+
+    let local = LocalRc::new(0u64);
+    assert_eq!(
+        local.allocation_size(),
+        mem::size_of::<LocalMeta>() + EXTRA + 8
+    );
+    let hybrid = LocalHybridRc::new(0u64);
+    assert_eq!(
+        hybrid.allocation_size(),
+        mem::size_of::<HybridMeta<LocalMode>>() + EXTRA + 8
+    );
+
+    // Slices include every element, padded out to the alignment of the header
+    let slice = SharedRc::<[u8]>::from_str_ref("hello");
+    let expected =
+        (mem::size_of::<SharedMeta>() + EXTRA + 5).next_multiple_of(mem::align_of::<SharedMeta>());
+    assert_eq!(slice.allocation_size(), expected);
+    assert_eq!(
+        slice.allocation_size(),
+        slice_layout::<SharedMeta, LocalMeta, u8>(5).unwrap().size()
+    );
+}
+
+#[cfg(feature = "weak")]
+#[test]
+fn weak_identity() {
+    let local = LocalRc::new(String::from("test"));
+    let other = LocalRc::new(String::from("test"));
+    let weak = local.downgrade();
+    let weak2 = local.downgrade();
+    let other_weak = other.downgrade();
+    assert!(weak == weak2);
+    assert!(weak != other_weak);
+
+    // Identity remains after the data is gone
+    drop(local);
+    assert!(weak.ptr_eq(&weak2));
+
+    let dangling = LocalWeak::<String>::new();
+    assert!(dangling == LocalWeak::default());
+    assert!(dangling != weak);
+    assert!(dangling.upgrade().is_none());
+    assert_eq!(dangling.strong_count(), 0);
+    assert_eq!(dangling.weak_count(), 0);
+}
+
+#[cfg(not(feature = "str_deref"))]
+#[test]
+fn from_vec() {
+    let bytes = SharedRc::<[u8]>::from_vec(alloc::vec![1, 2, 3]);
+    assert_eq!(bytes.as_bytes(), [1, 2, 3]);
+
+    let strings = LocalRc::<[String]>::from_vec(alloc::vec![String::from("a"), String::from("b")]);
+    assert_eq!(
+        strings.allocation_size(),
+        slice_layout::<LocalMeta, SharedMeta, String>(2)
+            .unwrap()
+            .size()
+    );
+}
+
+#[test]
+fn into_shared_tree() {
+    let leaf = LocalRc::new(String::from("leaf"));
+    let other = LocalRc::new(String::from("other"));
+    let tree = LocalRc::<[LocalRc<String>]>::from_vec_priv(alloc::vec![leaf.clone(), other]);
+    let tree2 = tree.clone();
+
+    // `tree` isn't unique so its elements are cloned, while `tree2` is moved
+    let shared = tree.into_shared();
+    let shared2 = tree2.into_shared();
+    let (data, data2) = (&shared.as_inner().data, &shared2.as_inner().data);
+    assert_eq!(*data[0], "leaf");
+    assert_eq!(*data2[1], "other");
+    assert!(!data[0].ptr_eq(&data2[0]));
+    assert_eq!(leaf.strong_count(), 1);
+
+    fn assert_send<T: Send>(_: &T) {}
+    assert_send(&shared);
+}
+
+#[test]
+fn raw_counts() {
+    let local = LocalRc::new(String::from("test"));
+    let local2 = local.clone();
+    assert_eq!(local.as_inner().metadata.raw_count(), 2);
+    drop(local2);
+
+    let shared = local.try_into_other().ok().unwrap();
+    assert_eq!(shared.as_inner().metadata.raw_count(), 1);
+
+    let local = LocalHybridRc::new(String::from("test"));
+    let local2 = local.clone();
+    assert_eq!(local.as_inner().metadata.raw_count(), 2);
+
+    // The raw shared count includes the local present bit until the last local handle is gone
+    let shared = local.to_other();
+    assert!(shared.as_inner().metadata.raw_count() > 1);
+    drop(local);
+    drop(local2);
+    assert_eq!(shared.as_inner().metadata.raw_count(), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn shared_count_overflow_boundary() {
+    use crate::algorithm::MAX_SHARED_COUNT;
+    use std::panic::{self, AssertUnwindSafe};
+
+    let shared = SharedRc::new(5);
+    let meta = &shared.as_inner().metadata;
+
+    // Pretend the count sits just below the boundary: clones up to `MAX_SHARED_COUNT + 1` succeed
+    meta.set_raw_count(MAX_SHARED_COUNT - 1);
+    let clone1 = shared.clone();
+    let clone2 = shared.clone();
+    assert_eq!(meta.raw_count(), MAX_SHARED_COUNT + 1);
+
+    // The next clone is the first to fire (after its increment, which is why headroom is needed)
+    let result = panic::catch_unwind(AssertUnwindSafe(|| shared.clone()));
+    assert!(result.is_err());
+    assert_eq!(meta.raw_count(), MAX_SHARED_COUNT + 2);
+
+    // Restore the real count so the handles free the allocation normally
+    meta.set_raw_count(3);
+    drop((clone1, clone2));
+    assert_eq!(shared.strong_count(), 1);
+}
+
+#[cfg(not(feature = "str_deref"))]
+#[test]
+fn map_to() {
+    let bytes = SharedRc::<[u8]>::from_slice(b"abc");
+    let chars = bytes.map_to(|&b| b as char);
+    assert!(chars == ['a', 'b', 'c']);
+}
+
+#[cfg(all(feature = "std", not(feature = "str_deref")))]
+#[test]
+fn map_to_panic_drops_written() {
+    use std::cell::Cell;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    struct Tracked<'a>(&'a Cell<usize>);
+
+    impl Drop for Tracked<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    let dropped = Cell::new(0);
+    let src = LocalRc::<[u32]>::from_slice(&[1, 2, 3, 4]);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        src.map_to(|&n| {
+            if n == 3 {
+                panic!("map failed");
+            }
+            Tracked(&dropped)
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(dropped.get(), 2);
+}
+
+#[cfg(all(feature = "std", not(feature = "str_deref")))]
+#[test]
+fn from_slice_cloned_panic_drops_clones() {
+    use std::cell::Cell;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    struct PanicOnThird<'a> {
+        clones: &'a Cell<usize>,
+        dropped: &'a Cell<usize>,
+    }
+
+    impl Clone for PanicOnThird<'_> {
+        fn clone(&self) -> Self {
+            self.clones.set(self.clones.get() + 1);
+            if self.clones.get() == 3 {
+                panic!("clone failed");
+            }
+            Self { ..*self }
+        }
+    }
+
+    impl Drop for PanicOnThird<'_> {
+        fn drop(&mut self) {
+            self.dropped.set(self.dropped.get() + 1);
+        }
+    }
+
+    let (clones, dropped) = (Cell::new(0), Cell::new(0));
+    let src: Vec<_> = (0..4)
+        .map(|_| PanicOnThird {
+            clones: &clones,
+            dropped: &dropped,
+        })
+        .collect();
+
+    let result = catch_unwind(AssertUnwindSafe(|| LocalRc::from_slice_cloned(&src)));
+    assert!(result.is_err());
+    assert_eq!(dropped.get(), 2);
+
+    clones.set(usize::MAX / 2);
+    let rc = SharedRc::from_slice_cloned(&src[..2]);
+    assert_eq!(rc.len(), 2);
+    drop(rc);
+    assert_eq!(dropped.get(), 4);
+}
+
+#[test]
+fn from_box() {
+    let rc = LocalRc::from_box(alloc::boxed::Box::new(String::from("boxed")));
+    assert_eq!(*rc, "boxed");
+
+    let s: SharedRc<str> = alloc::boxed::Box::<str>::from("boxed").into();
+    assert_eq!(&*s, "boxed");
+
+    #[cfg(not(feature = "str_deref"))]
+    {
+        let boxed: alloc::boxed::Box<[String]> =
+            alloc::vec![String::from("a"), String::from("b")].into();
+        let rc = SharedRc::from_boxed_slice(boxed);
+        assert!(rc == [String::from("a"), String::from("b")]);
+    }
+}
+
+#[cfg(not(feature = "str_deref"))]
+#[test]
+fn new_array() {
+    let rc = LocalRc::new_array([String::from("a"), String::from("b")]);
+    assert!(rc == [String::from("a"), String::from("b")]);
+
+    let empty = SharedRc::<[String]>::new_array([]);
+    assert!(empty.is_empty());
+}
+
+#[cfg(feature = "pool")]
+#[test]
+fn pooled_reuse() {
+    let rc = SharedRc::new_pooled([1u64; 3]);
+    let rc2 = rc.clone();
+    let addr = rc.identity();
+    drop(rc);
+    drop(rc2);
+
+    // The freed allocation is recycled with fresh counters
+    let rc = SharedRc::new_pooled([2u64; 3]);
+    assert_eq!(rc.identity(), addr);
+    assert_eq!(rc.strong_count(), 1);
+    assert_eq!(*rc, [2; 3]);
+
+    // Handles created without the pool are recycled too once the layout is pooled
+    let local = LocalRc::new([3u64; 3]);
+    let addr = local.identity();
+    drop(local);
+    assert_eq!(LocalRc::new_pooled([4u64; 3]).identity(), addr);
+}
+
+#[test]
+fn nested_drop_order() {
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+
+    struct Logged<'a>(&'a str, &'a RefCell<Vec<&'a str>>);
+
+    impl Drop for Logged<'_> {
+        fn drop(&mut self) {
+            self.1.borrow_mut().push(self.0);
+        }
+    }
+
+    let log = RefCell::new(Vec::new());
+    let kept = SharedRc::new(Logged("kept", &log));
+    let outer = SharedRc::new((
+        Logged("outer", &log),
+        alloc::vec![SharedRc::new(Logged("inner", &log)), kept.clone()],
+    ));
+    let outer2 = outer.clone();
+
+    drop(outer);
+    assert!(log.borrow().is_empty());
+
+    // The last handle drops the outer data, which drops the nested handles, all before returning
+    drop(outer2);
+    assert_eq!(*log.borrow(), ["outer", "inner"]);
+    assert_eq!(kept.strong_count(), 1);
+
+    drop(kept);
+    assert_eq!(*log.borrow(), ["outer", "inner", "kept"]);
+}
+
+#[cfg(not(feature = "str_deref"))]
+#[test]
+fn filtered() {
+    let numbers = SharedRc::<[u32]>::from_slice(&[1, 2, 3, 4, 5, 6]);
+    let even = numbers.filtered(|n| n % 2 == 0);
+    assert!(even == [2, 4, 6]);
+    assert!(numbers.filtered(|_| false).is_empty());
+
+    let strings = LocalRc::<[String]>::from_vec(alloc::vec![String::from("a"), String::from("bb")]);
+    let long = strings.filtered(|s| s.len() > 1);
+    assert_eq!(long.as_inner().data[0], "bb");
+}
+
+#[cfg(feature = "str_deref")]
+#[test]
+fn cow_str() {
+    use alloc::borrow::Cow;
+
+    let s = SharedStr::from_str_ref("borrowed");
+    assert!(matches!(s.as_cow_str(), Cow::Borrowed("borrowed")));
+
+    let owned = s.clone().into_cow_owned();
+    drop(s);
+    assert!(matches!(owned, Cow::Owned(ref o) if o == "borrowed"));
+}
+
+#[cfg(not(feature = "str_deref"))]
+#[test]
+fn slice_into_other() {
+    let local = LocalRc::<[String]>::from_vec(alloc::vec![String::from("a"), String::from("b")]);
+    let addr = local.identity();
+
+    // Unique: the allocation is reused and the length is kept
+    let shared = local.into_other_slice();
+    assert_eq!(shared.identity(), addr);
+    assert_eq!(shared.len(), 2);
+
+    // Not unique: the elements are cloned into a new allocation
+    let local = shared.to_other_slice();
+    assert_ne!(local.identity(), addr);
+    assert_eq!(local.as_inner().data, ["a", "b"]);
+
+    let shared2 = shared.clone();
+    let local2 = shared2.into_other_slice();
+    assert_ne!(local2.identity(), addr);
+    assert_eq!(local2.as_inner().data, ["a", "b"]);
+    assert_eq!(shared.strong_count(), 1);
+
+    let back = local2.into_other_slice();
+    assert_eq!(back.as_inner().data[1], "b");
+}
+
+#[test]
+fn new_with_meta() {
+    // SAFETY: A freshly created meta describes a single strong reference
+    let shared = unsafe { SharedRc::new_with_meta(String::from("test"), SharedMeta::create()) };
+    let shared2 = shared.clone();
+    assert_eq!(shared.strong_count(), 2);
+    drop(shared2);
+
+    let local = shared.try_into_other().ok().unwrap();
+    assert_eq!(*local, "test");
+    assert!(local.is_unique());
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "single strong reference")]
+fn new_with_meta_rejects_shared_meta() {
+    let meta = SharedMeta::create();
+    meta.clone();
+
+    // SAFETY: Not actually safe, but the debug check panics before the handle is created
+    let _ = unsafe { SharedRc::new_with_meta(1, meta) };
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn str_as_path() {
+    use std::path::Path;
+
+    fn file_name(path: impl AsRef<Path>) -> Option<String> {
+        Some(path.as_ref().file_name()?.to_str()?.into())
+    }
+
+    let path = SharedRc::<str>::from("config/app.toml");
+    assert_eq!(file_name(&path).as_deref(), Some("app.toml"));
+
+    #[cfg(feature = "str_deref")]
+    assert_eq!(
+        file_name(SharedStr::from_str_ref("/tmp/x.log")).as_deref(),
+        Some("x.log")
+    );
+}
+
+#[cfg(not(feature = "str_deref"))]
+#[test]
+fn shared_buf_freeze() {
+    let mut buf = SharedBuf::with_capacity(4);
+    buf.extend_from_slice(b"abc");
+    assert_eq!(buf.capacity(), 4);
+
+    // Growing keeps what was written so far
+    buf.extend_from_slice(b"defgh");
+    buf.push(b'!');
+    assert!(buf.capacity() >= 9);
+    assert_eq!(&*buf, b"abcdefgh!");
+
+    let frozen = buf.freeze();
+    let frozen2 = frozen.clone();
+    assert_eq!(frozen2.as_bytes(), b"abcdefgh!");
+    assert_eq!(frozen.strong_count(), 2);
+
+    assert!(LocalBuf::new().freeze().is_empty());
+}
+
+#[cfg(all(feature = "std", not(feature = "str_deref")))]
+#[test]
+fn shared_buf_io_write() {
+    use std::io::Write;
+
+    let mut buf = LocalBuf::new();
+    write!(buf, "{}-{}", 1, 2).unwrap();
+    assert_eq!(buf.freeze().as_bytes(), b"1-2");
+}
+
+#[test]
+fn map_value() {
+    let shared = SharedRc::new(String::from("test"));
+    let shared2 = shared.clone();
+
+    let len = FlexRc::map(shared, |s| s.len());
+    assert_eq!(*len, 4);
+    assert_eq!(shared2.strong_count(), 1);
+
+    let upper = LocalRc::map(LocalRc::<str>::from("abc"), |s| s.to_uppercase());
+    assert_eq!(*upper, "ABC");
+}
+
+#[test]
+fn try_map_value() {
+    let shared = SharedRc::<str>::from("42");
+    let shared2 = shared.clone();
+
+    let num = FlexRc::try_map(shared, |s| s.parse::<u32>()).unwrap();
+    assert_eq!(*num, 42);
+    assert_eq!(shared2.strong_count(), 1);
+
+    assert!(LocalRc::try_map(LocalRc::<str>::from("x"), |s| s.parse::<u32>()).is_err());
+}
+
+#[cfg(not(feature = "str_deref"))]
+#[test]
+fn from_box_dyn() {
+    use core::borrow::Borrow;
+    use core::fmt::Debug;
+
+    let offset = 10u64;
+    let add: Box<dyn Fn(u64) -> u64 + Send + Sync> = Box::new(move |x| x + offset);
+    let shared = SharedRc::from_box_dyn(add);
+    let shared2 = shared.clone();
+    let f: &(dyn Fn(u64) -> u64 + Send + Sync) = shared2.borrow();
+    assert_eq!(f(5), 15);
+
+    fn assert_send<T: Send>(_: &T) {}
+    assert_send(&shared);
+
+    // Dropping the last handle drops the value through its vtable
+    struct Logged<'a>(&'a str, &'a core::cell::Cell<bool>);
+
+    impl Debug for Logged<'_> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.write_str(self.0)
+        }
+    }
+
+    impl Drop for Logged<'_> {
+        fn drop(&mut self) {
+            self.1.set(true);
+        }
+    }
+
+    let dropped = core::cell::Cell::new(false);
+    let logged: Box<dyn Debug + '_> = Box::new(Logged("dyn", &dropped));
+    let local = LocalRc::from_box_dyn(logged);
+    let debug: &dyn Debug = local.borrow();
+    assert_eq!(alloc::format!("{debug:?}"), "dyn");
+    assert!(!dropped.get());
+    drop(local);
+    assert!(dropped.get());
+
+    // Over-aligned values end up at a larger offset from the header
+    #[repr(align(64))]
+    struct Aligned(u8);
+
+    impl Debug for Aligned {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    let aligned: Box<dyn Debug> = Box::new(Aligned(7));
+    let local = LocalRc::from_box_dyn(aligned);
+    let debug: &dyn Debug = local.borrow();
+    assert_eq!(debug as *const dyn Debug as *const u8 as usize % 64, 0);
+    assert_eq!(alloc::format!("{debug:?}"), "7");
+}
+
+#[cfg(not(feature = "str_deref"))]
+#[test]
+fn from_box_dyn_slice() {
+    let bytes: Box<[u16]> = Box::new([1, 2, 3]);
+    let shared = SharedRc::from_box_dyn(bytes);
+    assert!(shared == [1, 2, 3]);
+    assert!(shared.clone() == [1, 2, 3]);
+
+    // The length must come through, even for empty slices and zero sized elements
+    let empty: Box<[u16]> = Box::new([]);
+    assert!(LocalRc::from_box_dyn(empty).is_empty());
+    let unit: Box<[()]> = alloc::vec![(); 5].into_boxed_slice();
+    assert_eq!(LocalRc::from_box_dyn(unit).len(), 5);
+
+    let strings: Box<[String]> =
+        alloc::vec![String::from("a"), String::from("b")].into_boxed_slice();
+    let local = LocalRc::from_box_dyn(strings);
+    assert_eq!(local.as_inner().data[1], "b");
+}
+
+#[cfg(not(feature = "str_deref"))]
+#[test]
+fn downcast_into_local() {
+    use core::any::Any;
+
+    fn any(value: impl Any + Send + Sync) -> SharedRc<dyn Any + Send + Sync> {
+        let boxed: Box<dyn Any + Send + Sync> = Box::new(value);
+        SharedRc::from_box_dyn(boxed)
+    }
+
+    // Wrong type: the original handle comes back
+    let shared = any(String::from("plugin"));
+    let shared = shared.downcast_into_local::<u32>().err().unwrap();
+
+    // Right type, but not unique
+    let shared2 = shared.clone();
+    let shared = shared.downcast_into_local::<String>().err().unwrap();
+    drop(shared2);
+
+    let mut local = shared.downcast_into_local::<String>().ok().unwrap();
+    local.get_mut().unwrap().push('!');
+    assert_eq!(*local, "plugin!");
+
+    // Over-aligned data keeps its offset through the downcast
+    #[repr(align(32))]
+    struct Aligned(u8);
+
+    let shared = any(Aligned(7)).downcast::<Aligned>().ok().unwrap();
+    assert_eq!(shared.0, 7);
+    assert!(any(Aligned(7)).downcast::<u8>().is_err());
+}
+
+#[test]
+fn uninit_shared_init() {
+    let mut uninit = UninitShared::<String>::new(3);
+    for (i, elem) in uninit.iter_mut().enumerate() {
+        elem.write(alloc::format!("{i}"));
+    }
+
+    // SAFETY: Every element was written above
+    let shared = unsafe { uninit.assume_init() };
+    assert_eq!(shared.as_inner().data, ["0", "1", "2"]);
+    assert_eq!(shared.strong_count(), 1);
+}
+
+#[cfg(all(feature = "bytemuck", not(feature = "str_deref")))]
+#[test]
+fn cast_elements() {
+    let signed = SharedRc::<[i32]>::from_slice(&[-1, 0, 7]);
+    let signed2 = signed.clone();
+    let addr = signed.identity();
+
+    let unsigned = signed.cast_elements::<u32>();
+    assert_eq!(unsigned.identity(), addr);
+    assert!(unsigned == [u32::MAX, 0, 7]);
+    assert_eq!(unsigned.strong_count(), 2);
+    assert!(signed2 == [-1, 0, 7]);
+}
+
+#[cfg(feature = "track_location")]
+#[test]
+fn alloc_location() {
+    let line = line!() + 1;
+    let shared = SharedRc::new(1u8);
+    assert_eq!(shared.alloc_location().line(), line);
+    assert_eq!(shared.alloc_location().file(), file!());
+
+    let line = line!() + 1;
+    let slice = LocalRc::<[u8]>::from_str_ref("abc");
+    assert_eq!(slice.clone().alloc_location().line(), line);
+
+    let line = line!() + 1;
+    let mapped = FlexRc::try_map(shared, |n| Ok::<_, ()>(*n + 1)).unwrap();
+    assert_eq!(mapped.alloc_location().line(), line);
+}
+
+#[test]
+fn convert_error_reason() {
+    let local = LocalRc::new(String::from("test"));
+    let local2 = local.clone();
+    let err = local.try_into_shared().err().unwrap();
+    assert!(matches!(err, ConvertError::NotUnique(_)));
+    let local = err.into_inner();
+    drop(local2);
+    let shared = local.try_into_shared().ok().unwrap();
+
+    let shared2 = shared.clone();
+    let shared = match shared.try_into_local() {
+        Err(ConvertError::NotUnique(shared)) => shared,
+        _ => panic!("expected NotUnique"),
+    };
+    drop(shared2);
+    assert_eq!(*shared.try_into_local().ok().unwrap(), "test");
+
+    let hybrid = SharedHybridRc::new(1u8);
+    let _hybrid2 = hybrid.clone();
+    let local = hybrid.try_into_local().ok().unwrap();
+    assert_eq!(*local, 1);
+
+    // The local slot is taken, so without thread tracking a second conversion must fail
+    #[cfg(not(feature = "track_threads"))]
+    {
+        let hybrid2 = _hybrid2;
+        let err = hybrid2.try_into_local().err().unwrap();
+        assert_eq!(
+            alloc::format!("{err}"),
+            "a local handle to the data already exists"
+        );
+        assert!(matches!(err, ConvertError::LocalTaken(_)));
+    }
+}
+
+#[test]
+fn replace_unique() {
+    let mut rc = SharedRc::new(String::from("old"));
+    assert_eq!(rc.replace(String::from("new")), Ok(String::from("old")));
+    assert_eq!(*rc, "new");
+
+    let rc2 = rc.clone();
+    assert_eq!(
+        rc.replace(String::from("newer")),
+        Err(String::from("newer"))
+    );
+    assert_eq!(*rc2, "new");
+}
+
+#[test]
+fn into_iter_unique_and_shared() {
+    let make = || {
+        let mut uninit = UninitShared::<String>::new(3);
+        for (elem, s) in uninit.iter_mut().zip(["a", "b", "c"]) {
+            elem.write(String::from(s));
+        }
+        // SAFETY: Every element was written above
+        unsafe { uninit.assume_init() }
+    };
+
+    // Unique: elements are moved out, and the ones never yielded are dropped with the iterator
+    let mut iter = make().into_iter();
+    assert!(iter.is_unique());
+    assert_eq!(iter.len(), 3);
+    assert_eq!(iter.next_back().as_deref(), Some("c"));
+    assert_eq!(iter.as_slice(), ["a", "b"]);
+    assert_eq!(iter.next().as_deref(), Some("a"));
+    drop(iter);
+
+    // Shared: elements are cloned, and the other handle is left untouched
+    let rc = make();
+    let rc2 = rc.clone();
+    let iter = rc.into_iter();
+    assert!(!iter.is_unique());
+    assert_eq!(iter.collect::<Vec<_>>(), ["a", "b", "c"]);
+    assert_eq!(rc2.strong_count(), 1);
+}
+
+#[test]
+fn local_hybrid_into_shared_rc() {
+    let local = LocalHybridRc::new(String::from("test"));
+    let local2 = local.clone();
+    let shared: SharedRc<String> = local.into_shared_rc();
+
+    assert_eq!(*shared, "test");
+    assert_eq!(local2.strong_count(), 1);
+    assert!(shared.is_unique());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn max_count_boundaries() {
+    use std::panic::{self, AssertUnwindSafe};
+
+    assert_eq!(LocalMeta::MAX_COUNT, usize::MAX);
+    assert_eq!(HybridMeta::<LocalMode>::MAX_COUNT, u32::MAX as usize);
+
+    // A local handle can be cloned up to the max, and no further
+    #[cfg(not(feature = "local_unchecked"))]
+    {
+        let local = LocalRc::new_with_raw_count(5, LocalMeta::MAX_COUNT - 1);
+        let clone = local.clone();
+        assert_eq!(local.strong_count(), LocalMeta::MAX_COUNT);
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| local.clone())).is_err());
+        local.as_inner().metadata.set_raw_count(2);
+        drop(clone);
+    }
+
+    // Shared handles behave the same, with the check made after the increment
+    let shared = SharedRc::new_with_raw_count(5, SharedMeta::<ArcOrderings>::MAX_COUNT - 1);
+    let clone = shared.clone();
+    assert_eq!(shared.strong_count(), SharedMeta::<ArcOrderings>::MAX_COUNT);
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| shared.clone())).is_err());
+    shared.as_inner().metadata.set_raw_count(2);
+    drop(clone);
+}
+
+#[cfg(feature = "weak")]
+#[test]
+fn weak_list_notify_prunes() {
+    let first = LocalRc::new(1);
+    let second = LocalRc::new(2);
+    let third = LocalRc::new(3);
+
+    let mut observers = LocalWeakList::new();
+    observers.push(&first);
+    observers.push(&second);
+    observers.push_weak(third.downgrade());
+    drop(second);
+
+    let mut seen = Vec::new();
+    assert_eq!(observers.notify(|value| seen.push(*value)), 2);
+    assert_eq!(seen, [1, 3]);
+    assert_eq!(observers.len(), 2);
+
+    drop(third);
+    observers.prune();
+    assert_eq!(observers.len(), 1);
+    assert_eq!(first.weak_count(), 1);
+}
+
+#[test]
+#[should_panic(expected = "exceeds isize::MAX bytes")]
+fn slice_len_over_isize_max() {
+    let _ = SharedRc::<[u64]>::new_slice_uninit(isize::MAX as usize / 8 + 1);
+}
+
+#[test]
+fn try_slice_len_over_isize_max() {
+    assert!(SharedRc::<[u64]>::try_new_slice_uninit_inner(isize::MAX as usize / 8 + 1).is_err());
+    assert!(SharedRc::<[u8]>::try_new_slice_uninit_inner(usize::MAX).is_err());
+}
+
+#[test]
+fn scatter_clones() {
+    use core::ptr;
+    use mem::MaybeUninit;
+
+    let shared = SharedRc::new(String::from("test"));
+    let mut slots: [MaybeUninit<SharedRc<String>>; 4] = [const { MaybeUninit::uninit() }; 4];
+    let clones = shared.scatter(&mut slots);
+    assert!(clones.iter().all(|clone| clone.ptr_eq(&shared)));
+    assert_eq!(shared.strong_count(), 5);
+
+    // SAFETY: Each clone is dropped exactly once and the slots are never read again
+    unsafe { ptr::drop_in_place(clones) };
+    assert_eq!(shared.strong_count(), 1);
+
+    let local = LocalRc::new(1);
+    let mut slots = [const { MaybeUninit::uninit() }; 3];
+    let clones = local.scatter(&mut slots);
+    assert_eq!(local.strong_count(), 4);
+    // SAFETY: As above
+    unsafe { ptr::drop_in_place(clones) };
+    assert_eq!(local.strong_count(), 1);
+}
+
+// The allocation is freed with `Layout::for_value` on the fat pointer, so the slice length must
+// reach the deallocator intact. Under Miri a free with the wrong size is reported as UB
+#[test]
+fn drop_large_slices() {
+    const LEN: usize = 64 * 1024;
+
+    let big = SharedRc::<[u8]>::from_str_ref("x".repeat(LEN));
+    let expected = slice_layout::<SharedMeta, LocalMeta, u8>(LEN)
+        .unwrap()
+        .size();
+    assert_eq!(big.allocation_size(), expected);
+    let clone = big.clone();
+    drop(big);
+    drop(clone);
+
+    // Shrinking reallocates, so the freed size must follow the new length rather than the old one
+    let mut uninit = SharedRc::<[u64]>::new_slice_uninit(LEN);
+    // SAFETY: We are the only owner, and only the written elements are kept
+    let shrunk = unsafe {
+        for (idx, elem) in uninit.get_mut_unchecked()[..10].iter_mut().enumerate() {
+            elem.write(idx as u64);
+        }
+        uninit.set_len_and_assume_init(10)
+    };
+    let expected = slice_layout::<SharedMeta, LocalMeta, u64>(10)
+        .unwrap()
+        .size();
+    assert_eq!(shrunk.allocation_size(), expected);
+    drop(shrunk);
+}
+
+#[cfg(feature = "bytemuck")]
+#[test]
+fn pod_as_bytes() {
+    let value = SharedRc::new(0x0102_0304u32);
+    assert_eq!(value.as_bytes(), 0x0102_0304u32.to_ne_bytes());
+
+    let pair = LocalRc::new([1u16, 2]);
+    assert_eq!(pair.as_bytes().len(), 4);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn local_freeze_then_share() {
+    let mut local = LocalRc::new(Vec::new());
+    local.get_mut().unwrap().extend([1, 2, 3]);
+
+    // Not unique, so it can't be frozen yet
+    let local2 = local.clone();
+    let local = local.freeze().err().unwrap();
+    drop(local2);
+
+    let shared = local.freeze().ok().unwrap();
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let shared = shared.clone();
+            std::thread::spawn(move || shared.iter().sum::<i32>())
+        })
+        .collect();
+
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 6);
+    }
+    assert_eq!(shared.strong_count(), 1);
+}
+
+#[cfg(feature = "weak")]
+#[test]
+fn weak_same_alloc() {
+    let rc = SharedRc::new(1);
+    let weak = rc.downgrade();
+    let weak2 = weak.clone();
+    let other = SharedRc::new(1).downgrade();
+
+    assert!(weak.same_alloc(&weak2));
+    assert!(!weak.same_alloc(&other));
+    assert_eq!(rc.weak_count(), 2);
+
+    // Still the same allocation once the data is gone
+    drop(rc);
+    assert!(weak.same_alloc(&weak2));
+
+    // Dangling handles have no allocation to share
+    let dangling = SharedWeak::<i32>::new();
+    assert!(!dangling.same_alloc(&SharedWeak::new()));
+    assert!(dangling.ptr_eq(&SharedWeak::new()));
+}
+
+#[cfg(not(feature = "str_deref"))]
+#[test]
+fn from_slices_gather() {
+    let gathered = SharedRc::<[u8]>::from_slices([&b"ab"[..], b"", b"cde"]);
+    assert_eq!(gathered.as_bytes(), b"abcde");
+
+    // Owned chunks from a one-shot iterator
+    let owned = SharedRc::<[u8]>::from_slices((0..3u8).map(|n| alloc::vec![n; n as usize]));
+    assert_eq!(owned.as_bytes(), [1, 2, 2]);
+
+    let single = SharedRc::<[u8]>::from_slices(Some(b"single"));
+    assert_eq!(single.as_bytes(), b"single");
+
+    let empty = SharedRc::<[u8]>::from_slices(Vec::<&[u8]>::new());
+    assert!(empty.is_empty());
+    let all_empty = SharedRc::<[u8]>::from_slices([b"", b""]);
+    assert!(all_empty.is_empty());
+}
+
+// *** Layout self-tests ***
+
+// Conversions reinterpret one inner type as the other in place. These check at runtime what the
+// static assertions cover at compile time, plus that a value survives the round trip bit for bit
+
+// Checks that the data lands at the same offset in both inner types of a pair
+macro_rules! assert_same_data_offset {
+    ($meta:ty, $meta2:ty, $($t:ty),+) => {
+        $(assert_eq!(
+            mem::offset_of!(FlexRcInner<$meta, $meta2, $t>, data),
+            mem::offset_of!(FlexRcInner<$meta2, $meta, $t>, data),
+            "data offset differs for {}",
+            core::any::type_name::<$t>()
+        );)+
+    };
+}
+
+#[repr(align(16))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Aligned16(u8, u64);
+
+#[test]
+fn layout_meta_sizes_match() {
+    assert_eq!(mem::size_of::<LocalMeta>(), mem::size_of::<SharedMeta>());
+    assert_eq!(mem::align_of::<LocalMeta>(), mem::align_of::<SharedMeta>());
+    assert_eq!(
+        mem::size_of::<HybridMeta<LocalMode>>(),
+        mem::size_of::<HybridMeta<SharedMode>>()
+    );
+    assert_eq!(
+        mem::align_of::<HybridMeta<LocalMode>>(),
+        mem::align_of::<HybridMeta<SharedMode>>()
+    );
+}
+
+#[test]
+fn layout_data_offsets_match() {
+    assert_same_data_offset!(LocalMeta, SharedMeta, u8, u64, u128, [u8; 3], Aligned16);
+    assert_same_data_offset!(
+        HybridMeta<LocalMode>,
+        HybridMeta<SharedMode>,
+        u8,
+        u64,
+        u128,
+        [u8; 3],
+        Aligned16
+    );
+}
+
+#[test]
+fn layout_conversion_preserves_bits() {
+    let value = Aligned16(0xA5, 0x0123_4567_89AB_CDEF);
+
+    let local = LocalRc::new(value);
+    let shared = local.try_into_other().ok().unwrap();
+    assert_eq!(*shared, value);
+    let local = shared.try_into_other().ok().unwrap();
+    assert_eq!(*local, value);
+
+    let local = LocalHybridRc::new(value);
+    let shared = local.try_into_other().ok().unwrap();
+    assert_eq!(*shared, value);
+    assert_eq!(shared.strong_count(), 1);
+
+    // Slices carry their length in the fat pointer, which must survive as well
+    let mut uninit = UninitLocal::<u128>::new(4);
+    for (idx, elem) in uninit.iter_mut().enumerate() {
+        elem.write(u128::MAX / (idx as u128 + 1));
+    }
+    // SAFETY: Every element was written above
+    let local = unsafe { uninit.assume_init() };
+    let shared = local.try_into_other().ok().unwrap();
+    assert_eq!(shared.len(), 4);
+    assert_eq!(shared.allocation_size(), local_slice_size::<u128>(4));
+    for (idx, elem) in shared.as_inner().data.iter().enumerate() {
+        assert_eq!(*elem, u128::MAX / (idx as u128 + 1));
+    }
+}
+
+fn local_slice_size<T>(len: usize) -> usize {
+    slice_layout::<LocalMeta, SharedMeta, T>(len)
+        .unwrap()
+        .size()
+}
+
+#[test]
+fn from_owned_moves() {
+    // A type that can't be cloned can still be moved in
+    struct NoClone(String);
+
+    let owned = SharedRc::from_owned(NoClone(String::from("test")));
+    assert_eq!(owned.0, "test");
+
+    let s = String::from("test");
+    let by_ref = LocalRc::from_ref(&s);
+    assert_eq!(*by_ref, s);
+}
+
+#[test]
+fn get_mut_or_copies_when_shared() {
+    // Not `Clone`, so only a custom copy can detach it
+    struct Doc(String);
+
+    let mut doc = LocalRc::new(Doc(String::from("a")));
+    doc.get_mut_or(|_| unreachable!()).0.push('b');
+    assert_eq!(doc.0, "ab");
+
+    let before = doc.clone();
+    doc.get_mut_or(|d| Doc(d.0.clone())).0.push('c');
+    assert_eq!(doc.0, "abc");
+    assert_eq!(before.0, "ab");
+    assert!(!doc.ptr_eq(&before));
+
+    let mut n = SharedRc::new(1);
+    let other = n.clone();
+    *n.to_mut_owned() += 1;
+    assert_eq!((*n, *other), (2, 1));
+    *n.to_mut_owned() += 1;
+    assert_eq!(*n, 3);
+}
+
+#[test]
+fn hybrid_try_clone_local() {
+    let shared = SharedHybridRc::new(5);
+    let local = shared.try_clone_local().unwrap();
+    assert_eq!(*local, 5);
+    assert_eq!(shared.strong_count(), 2);
+    assert!(shared.is_local_present());
+
+    // Only the owning thread can add more local handles, and only if it's tracked
+    #[cfg(feature = "track_threads")]
+    assert!(shared.try_clone_local().is_some());
+    #[cfg(not(feature = "track_threads"))]
+    assert!(shared.try_clone_local().is_none());
+
+    drop(local);
+    assert!(!shared.is_local_present());
+    assert!(shared.try_clone_local().is_some());
+    assert_eq!(shared.strong_count(), 1);
+}
+
+#[test]
+fn from_blanket_into() {
+    // Generic code bounded on `Into` accepts plain values
+    fn wrap<V: Into<SharedRc<u32>>>(value: V) -> SharedRc<u32> {
+        value.into()
+    }
+    assert_eq!(*wrap(5), 5);
+    assert_eq!(*SharedRc::from(5), 5);
+
+    // The blanket impl and the `str` impls both apply to `String` without overlapping, since the
+    // `str` impls target an unsized `T` the blanket one can never cover: the target type decides
+    let copied: LocalRc<str> = String::from("a").into();
+    let moved: LocalRc<String> = String::from("a").into();
+    assert_eq!(&*copied, moved.as_str());
+    let moved: SharedRc<&str> = "a".into();
+    assert_eq!(*moved, "a");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn interner_hit_miss_reclaim() {
+    let interner = FlexInterner::<String>::new();
+
+    // Hit: equal values share an allocation
+    let a = interner.intern(String::from("a"));
+    let a2 = interner.intern(String::from("a"));
+    assert!(a.ptr_eq(&a2));
+
+    // Miss: a different value gets its own
+    let b = interner.intern(String::from("b"));
+    assert!(!a.ptr_eq(&b));
+    assert_eq!(interner.len(), 2);
+
+    // Reclaim: once the last handle is gone the entry is removed
+    drop(b);
+    #[cfg(feature = "weak")]
+    {
+        // The interner doesn't keep the value alive
+        assert_eq!(a.strong_count(), 2);
+        assert_eq!(interner.purge(), 1);
+    }
+    #[cfg(not(feature = "weak"))]
+    {
+        assert_eq!(a.strong_count(), 3);
+        assert_eq!(interner.purge(), 1);
+    }
+    assert_eq!(interner.len(), 1);
+
+    drop(a);
+    drop(a2);
+    assert_eq!(interner.purge(), 1);
+    assert!(interner.is_empty());
+    assert_eq!(*interner.intern(String::from("a")), "a");
+
+    let strs = FlexInterner::<[u8]>::new();
+    let s = strs.intern("str");
+    assert!(s.ptr_eq(&strs.intern(String::from("str"))));
+    assert_eq!(s.as_bytes(), b"str");
+}
+
+#[cfg(all(feature = "std", feature = "weak"))]
+#[test]
+fn interner_drops_values_with_last_handle() {
+    let interner = FlexInterner::<u32>::new();
+    let value = interner.intern(5u32);
+    let weak = value.downgrade();
+    drop(value);
+
+    // Dropped right away, even before the dead entry is removed
+    assert!(weak.upgrade().is_none());
+    assert_eq!(interner.len(), 1);
+
+    // Interning an equal value replaces the dead entry
+    let value = interner.intern(5u32);
+    assert!(!weak.same_alloc(&value.downgrade()));
+    assert_eq!(interner.len(), 1);
+}
+
+#[test]
+fn hybrid_shared_clone_with_local_present() {
+    // The local present bit lives in the shared counter, so the overflow check has to ignore it
+    let local = LocalHybridRc::new(1);
+    let shared = local.to_other();
+    assert!(shared.is_local_present());
+
+    let clones: alloc::vec::Vec<_> = (0..3).map(|_| shared.clone()).collect();
+    assert_eq!(shared.strong_count(), 5);
+    drop(clones);
+    assert_eq!(*shared, 1);
+    assert_eq!(local.strong_count(), 2);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "misaligned")]
+fn dealloc_inner_rejects_misaligned() {
+    let ptr = core::ptr::NonNull::<FlexRcInner<LocalMeta, SharedMeta, u64>>::dangling();
+    let ptr =
+        (ptr.as_ptr() as *mut u8).wrapping_add(1) as *mut FlexRcInner<LocalMeta, SharedMeta, u64>;
+
+    // SAFETY: Not actually safe, but the debug check panics before anything is dereferenced
+    unsafe { dealloc_inner(core::ptr::NonNull::new_unchecked(ptr)) }
+}
+
+#[test]
+fn strong_count_relaxed_snapshot() {
+    let shared = SharedRc::new(1u8);
+    let shared2 = shared.clone();
+    assert_eq!(shared.strong_count_relaxed(), 2);
+    drop(shared2);
+    assert_eq!(shared.strong_count_relaxed(), shared.strong_count());
+
+    let local = LocalRc::new(1u8);
+    let local2 = local.clone();
+    assert_eq!(local.strong_count_relaxed(), 2);
+    drop(local2);
+    assert_eq!(local.strong_count_relaxed(), 1);
+
+    // The local present bit must never show up in a count: from the shared side all local handles
+    // count as one, while the local side adds the shared handles to its own count
+    let shared = SharedHybridRc::new(1u8);
+    let local = shared.to_other();
+    let local2 = local.clone();
+    assert_eq!(shared.strong_count_relaxed(), 2);
+    assert_eq!(local.strong_count_relaxed(), 3);
+    assert_eq!(local.strong_count_relaxed(), local.strong_count());
+    drop(local2);
+    drop(local);
+    assert_eq!(shared.strong_count_relaxed(), 1);
+}
+
+#[test]
+fn as_bytes_as_str() {
+    let valid = SharedRc::<[u8]>::from_str_ref("h\u{e9}llo");
+    assert_eq!(valid.as_bytes(), "h\u{e9}llo".as_bytes());
+    assert_eq!(valid.as_str(), Ok("h\u{e9}llo"));
+
+    // Nothing is validated up front, so invalid bytes are only reported by `as_str`
+    #[cfg(not(feature = "str_deref"))]
+    {
+        let invalid = SharedRc::<[u8]>::from_slice(b"ab\xff");
+        assert_eq!(invalid.as_bytes(), b"ab\xff");
+        assert_eq!(invalid.as_str().err().unwrap().valid_up_to(), 2);
+    }
+}
+
+#[cfg(feature = "str_deref")]
+#[test]
+fn str_from_iter() {
+    let s: SharedStr = "abc".chars().collect();
+    assert_eq!(&*s, "abc");
+
+    // Multi-byte chars are encoded as UTF-8, so the length is in bytes
+    let s: LocalStr = "h\u{e9}llo".chars().collect();
+    assert_eq!(s.as_bytes(), "h\u{e9}llo".as_bytes());
+    assert_eq!(s.len(), 6);
+
+    let s: SharedStr = ["h\u{e9}", "", "llo"].into_iter().collect();
+    assert_eq!(&*s, "h\u{e9}llo");
+}
+
+#[test]
+fn try_from_str_ref_fallible() {
+    let s = LocalRc::<[u8]>::try_from_str_ref("h\u{e9}llo").ok();
+    assert_eq!(s.unwrap().as_bytes(), "h\u{e9}llo".as_bytes());
+    let empty = LocalRc::<[u8]>::try_from_str_ref("").ok();
+    assert!(empty.unwrap().as_bytes().is_empty());
+
+    // No string can be this long, so go through the shared fallible allocation path directly
+    let too_long = isize::MAX as usize + 1;
+    assert!(matches!(
+        LocalRc::<[u8]>::try_new_slice_uninit_inner(too_long),
+        Err(AllocError)
+    ));
+    assert!(matches!(
+        SharedRc::<[u64]>::try_new_slice_uninit_inner(too_long / 8),
+        Err(AllocError)
+    ));
+}
+
+#[test]
+fn slice_eq() {
+    let shared = SharedRc::<[u16]>::from_slice_priv(&[1, 2, 3]);
+    let same: &[u16] = &[1, 2, 3];
+    let other: &[u16] = &[1, 2, 4];
+    let shorter: &[u16] = &[1, 2];
+
+    assert!(shared == *same && shared != *other && shared != *shorter);
+    assert!(shared == same && shared != other && shared != shorter);
+    assert!(shared == [1, 2, 3] && shared != [1, 2, 4] && shared != [1, 2]);
+
+    let same: &[u16; 3] = &[1, 2, 3];
+    let other: &[u16; 3] = &[1, 2, 4];
+    let shorter: &[u16; 2] = &[1, 2];
+    assert!(shared == same && shared != other && shared != shorter);
+
+    #[cfg(feature = "str_deref")]
+    {
+        let path = SharedStr::from_str_ref("index.html");
+        assert!(path == "index.html" && path != "index.htm" && path != "index.html5");
+        assert!(path == *"index.html" && path != *"about.html");
+    }
+}
+
+#[test]
+fn from_utf8_validates() {
+    let s = SharedRc::<[u8]>::from_utf8("h\u{e9}llo".as_bytes());
+    assert_eq!(s.ok().unwrap().as_bytes(), "h\u{e9}llo".as_bytes());
+    let err = SharedRc::<[u8]>::from_utf8(b"ab\xffcd").err().unwrap();
+    assert_eq!(err.valid_up_to(), 2);
+
+    #[cfg(feature = "str_deref")]
+    {
+        let bytes: &[u8] = "h\u{e9}llo".as_bytes();
+        let s: SharedStr = bytes.try_into().ok().unwrap();
+        assert_eq!(&*s, "h\u{e9}llo");
+
+        let bytes: &[u8] = b"abc\xe9";
+        let result: Result<SharedStr, _> = bytes.try_into();
+        assert_eq!(result.err().unwrap().valid_up_to(), 3);
+    }
+}
+
+#[test]
+fn by_address_order() {
+    use core::cmp::Ordering;
+
+    // Equal values in two allocations, plus a second handle to the first one
+    let a = ByAddress(SharedRc::new(String::from("same")));
+    let b = ByAddress(SharedRc::new(String::from("same")));
+    let a2 = a.clone();
+    assert!(a.0 == b.0);
+
+    let addr = |h: &ByAddress<SharedMeta, LocalMeta, String>| h.identity();
+    let mut sorted = alloc::vec![b.clone(), a2.clone(), a.clone()];
+    sorted.sort();
+    assert!(sorted.windows(2).all(|w| addr(&w[0]) <= addr(&w[1])));
+
+    let handles = [&a, &a2, &b];
+    for x in handles {
+        for y in handles {
+            assert_eq!(x.cmp(y) == Ordering::Equal, x.0.ptr_eq(&y.0));
+            assert_eq!(x == y, x.0.ptr_eq(&y.0));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    {
+        use core::hash::{Hash, Hasher};
+
+        fn hash_of(value: &impl Hash) -> u64 {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+        assert_eq!(hash_of(&a), hash_of(&a2));
+    }
+}
+
+#[test]
+fn local_cell_shares_mutation() {
+    let cell = LocalCell::new(core::cell::RefCell::new(1));
+    let cell2 = cell.clone();
+    *cell2.borrow_mut() += 1;
+    assert_eq!(*cell.borrow(), 2);
+}
+
+#[test]
+#[should_panic]
+fn local_cell_borrow_mut_while_borrowed() {
+    let cell = LocalCell::new(core::cell::RefCell::new(1));
+    let _borrow = cell.borrow();
+    let _ = cell.borrow_mut();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn shared_mutex_try_lock() {
+    use std::sync::{Mutex, TryLockError};
+
+    let mutex = SharedMutex::new(Mutex::new(1));
+    let mutex2 = mutex.clone();
+    let guard = mutex.lock().unwrap();
+    assert!(matches!(mutex2.try_lock(), Err(TryLockError::WouldBlock)));
+    drop(guard);
+
+    *mutex2.try_lock().unwrap() += 1;
+    assert_eq!(*mutex.lock().unwrap(), 2);
+}
+
+#[test]
+fn try_borrow_mut_unique_only() {
+    let mut local = LocalRc::new(1);
+    *local.try_borrow_mut().unwrap() += 1;
+
+    let local2 = local.clone();
+    assert!(local.try_borrow_mut().is_none());
+    drop(local2);
+    assert_eq!(local.try_borrow_mut().copied(), Some(2));
+
+    #[cfg(feature = "weak")]
+    {
+        let weak = local.downgrade();
+        assert!(local.try_borrow_mut().is_none());
+        drop(weak);
+        assert!(local.try_borrow_mut().is_some());
+    }
+}
@@ -0,0 +1,132 @@
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+
+use crate::{Algorithm, FlexRc};
+
+// *** TotalOrd ***
+
+/// Floating point types that have a total order (IEEE 754 `totalOrder`, as in `f64::total_cmp`)
+pub trait TotalOrd: Copy {
+    fn total_cmp(&self, other: &Self) -> Ordering;
+
+    /// The raw bits of the value. Two values are equal under `total_cmp` iff their bits are equal
+    fn total_bits(&self) -> u64;
+}
+
+impl TotalOrd for f32 {
+    #[inline]
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f32::total_cmp(self, other)
+    }
+
+    #[inline]
+    fn total_bits(&self) -> u64 {
+        self.to_bits() as u64
+    }
+}
+
+impl TotalOrd for f64 {
+    #[inline]
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+
+    #[inline]
+    fn total_bits(&self) -> u64 {
+        self.to_bits()
+    }
+}
+
+// *** OrdByTotalCmp ***
+
+/// Wraps a handle to a float so that equality, hashing and ordering use its total order. Unlike
+/// the float's own `PartialOrd`, this is deterministic even for NaN and signed zeros, so handles
+/// can be sorted or used as `BTreeMap` keys
+pub struct OrdByTotalCmp<META, META2, T>(pub FlexRc<META, META2, T>)
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: TotalOrd;
+
+impl<META, META2, T> Clone for OrdByTotalCmp<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: TotalOrd,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<META, META2, T> Deref for OrdByTotalCmp<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: TotalOrd,
+{
+    type Target = FlexRc<META, META2, T>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<META, META2, T> PartialEq for OrdByTotalCmp<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: TotalOrd,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<META, META2, T> Eq for OrdByTotalCmp<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: TotalOrd,
+{
+}
+
+impl<META, META2, T> PartialOrd for OrdByTotalCmp<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: TotalOrd,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<META, META2, T> Ord for OrdByTotalCmp<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: TotalOrd,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        (*self.0).total_cmp(&*other.0)
+    }
+}
+
+impl<META, META2, T> Hash for OrdByTotalCmp<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: TotalOrd,
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.total_bits().hash(state)
+    }
+}
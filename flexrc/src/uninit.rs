@@ -0,0 +1,71 @@
+use core::mem;
+use core::ops::{Deref, DerefMut};
+
+use static_assertions::assert_not_impl_any;
+
+use crate::{Algorithm, FlexRc, LocalMeta, SharedMeta};
+
+assert_not_impl_any!(UninitShared<u8>: Clone);
+assert_not_impl_any!(UninitLocal<u8>: Clone);
+
+// *** UninitRc ***
+
+/// A slice of not yet initialized elements that can't be cloned (or downgraded) until
+/// `assume_init` turns it into a regular handle. Unlike the handle returned by `new_slice_uninit`,
+/// it is unique by construction, so writing the elements needs no `unsafe` and the only
+/// remaining obligation of `assume_init` is that every element was written
+pub struct UninitRc<META, META2, T>(FlexRc<META, META2, [mem::MaybeUninit<T>]>)
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>;
+
+/// An uninitialized slice that becomes a `LocalRc<[T]>`
+pub type UninitLocal<T> = UninitRc<LocalMeta, SharedMeta, T>;
+
+/// An uninitialized slice that becomes a `SharedRc<[T]>`
+pub type UninitShared<T> = UninitRc<SharedMeta, LocalMeta, T>;
+
+impl<META, META2, T> UninitRc<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    /// Allocates room for `len` elements
+    #[inline]
+    pub fn new(len: usize) -> Self {
+        Self(FlexRc::<META, META2, [T]>::new_slice_uninit(len))
+    }
+
+    /// # Safety
+    /// Every element must have been initialized (and with `str_deref`, `[u8]` data must be valid
+    /// UTF-8)
+    #[inline]
+    pub unsafe fn assume_init(self) -> FlexRc<META, META2, [T]> {
+        self.0.assume_init()
+    }
+}
+
+impl<META, META2, T> Deref for UninitRc<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    type Target = [mem::MaybeUninit<T>];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<META, META2, T> DerefMut for UninitRc<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: The handle is never cloned or downgraded, so we are the only owner
+        unsafe { self.0.data_mut() }
+    }
+}
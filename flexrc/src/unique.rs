@@ -0,0 +1,73 @@
+use core::ops::{Deref, DerefMut};
+
+use crate::{Algorithm, FlexRc};
+
+// *** UniqueGuard ***
+
+/// Exclusive access to the data of a uniquely owned handle. The guard mutably borrows the handle,
+/// so no new references can be created while it is alive, and it can be passed to APIs that take
+/// `impl AsMut<T>`
+pub struct UniqueGuard<'a, T: ?Sized>(&'a mut T);
+
+impl<T: ?Sized> Deref for UniqueGuard<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl<T: ?Sized> DerefMut for UniqueGuard<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0
+    }
+}
+
+impl<T: ?Sized> AsRef<T> for UniqueGuard<'_, T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self.0
+    }
+}
+
+impl<T: ?Sized> AsMut<T> for UniqueGuard<'_, T> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut T {
+        self.0
+    }
+}
+
+#[cfg(not(feature = "str_deref"))]
+impl<META, META2, T> FlexRc<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    /// Returns a guard giving exclusive access to the data if this is the only reference to it,
+    /// otherwise `None`. This is the same check as `get_mut`, but the guard can be handed to APIs
+    /// expecting `AsMut<T>`
+    #[inline]
+    pub fn as_unique_mut(&mut self) -> Option<UniqueGuard<'_, T>> {
+        self.get_mut().map(UniqueGuard)
+    }
+}
+
+// Same as above, but only for sized data since with `str_deref` `[u8]` data must stay valid UTF-8
+// (see `get_mut`)
+#[cfg(feature = "str_deref")]
+impl<META, META2, T> FlexRc<META, META2, T>
+where
+    META: Algorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    /// Returns a guard giving exclusive access to the data if this is the only reference to it,
+    /// otherwise `None`. This is the same check as `get_mut`, but the guard can be handed to APIs
+    /// expecting `AsMut<T>`
+    #[inline]
+    pub fn as_unique_mut(&mut self) -> Option<UniqueGuard<'_, T>> {
+        self.get_mut().map(UniqueGuard)
+    }
+}
@@ -0,0 +1,198 @@
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::ptr;
+use core::ptr::NonNull;
+
+use crate::{dealloc_inner, Algorithm, FlexRc, FlexRcInner, WeakAlgorithm};
+
+// Address of weak handles created by `FlexWeak::new` (same sentinel as std `Weak::new`)
+const DANGLING: usize = usize::MAX;
+
+// *** FlexWeak ***
+
+/// A non-owning handle to a `FlexRc` allocation. The data is destroyed once the last strong handle
+/// drops, but the allocation itself lives on until the last weak handle drops as well.
+///
+/// A weak handle ties its allocation to its metadata type: converting a strong handle into the other
+/// type of the pair (local -> shared, or shared -> local) fails while any weak handles exist, since
+/// upgrading one afterwards would create a strong handle of the old type next to the new one.
+///
+/// Equality and hashing are by identity (the allocation pointed to) rather than by value, since the
+/// value may already be gone. Dangling handles from `FlexWeak::new` all compare equal.
+#[repr(C)]
+pub struct FlexWeak<META, META2, T>
+where
+    META: WeakAlgorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    ptr: NonNull<FlexRcInner<META, META2, T>>,
+    phantom: PhantomData<FlexRcInner<META, META2, T>>,
+}
+
+impl<META, META2, T> FlexWeak<META, META2, T>
+where
+    META: WeakAlgorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    #[inline(always)]
+    pub(crate) fn from_inner(inner: NonNull<FlexRcInner<META, META2, T>>) -> Self {
+        Self {
+            ptr: inner,
+            phantom: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    fn addr(&self) -> usize {
+        // Only use the address and drop any fat pointer metadata
+        self.ptr.as_ptr() as *const u8 as usize
+    }
+
+    #[inline(always)]
+    fn metadata(&self) -> Option<&META> {
+        if self.addr() == DANGLING {
+            None
+        } else {
+            // SAFETY: The allocation lives as long as we hold a weak reference, but the data may
+            // have been dropped already, so we must never form a reference to the whole inner
+            unsafe { Some(&(*self.ptr.as_ptr()).metadata) }
+        }
+    }
+
+    /// Attempts to create a new strong handle, returning `None` if the data has already been dropped
+    #[inline]
+    pub fn upgrade(&self) -> Option<FlexRc<META, META2, T>> {
+        if self.metadata()?.upgrade() {
+            Some(FlexRc::from_inner(self.ptr))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of strong handles to this allocation
+    #[inline]
+    pub fn strong_count(&self) -> usize {
+        self.metadata().map_or(0, |meta| meta.strong_count())
+    }
+
+    /// Returns the number of weak handles to this allocation, or zero if no strong handles remain
+    #[inline]
+    pub fn weak_count(&self) -> usize {
+        match self.metadata() {
+            Some(meta) if meta.strong_count() > 0 => meta.weak_count(),
+            _ => 0,
+        }
+    }
+
+    /// Returns true if both handles point to the same allocation (or both are dangling)
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.addr() == other.addr()
+    }
+
+    /// Returns true if both handles point to the same real allocation, without touching any
+    /// counts. Unlike `ptr_eq`, dangling handles from `FlexWeak::new` have no allocation, so they
+    /// never match. The data may already have been dropped, so this doesn't mean `upgrade` will
+    /// succeed, only that both would upgrade to the same handle if it does
+    #[inline]
+    pub fn same_alloc(&self, other: &Self) -> bool {
+        self.addr() != DANGLING && self.addr() == other.addr()
+    }
+}
+
+impl<META, META2, T> FlexWeak<META, META2, T>
+where
+    META: WeakAlgorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    /// Creates a weak handle without an allocation. It never upgrades
+    #[inline]
+    pub fn new() -> Self {
+        // SAFETY: `DANGLING` isn't zero. It is never dereferenced (no allocation can start at the
+        // last address) - every access to the inner checks for it first
+        Self::from_inner(unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(DANGLING)) })
+    }
+}
+
+impl<META, META2, T> Default for FlexWeak<META, META2, T>
+where
+    META: WeakAlgorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<META, META2, T> Clone for FlexWeak<META, META2, T>
+where
+    META: WeakAlgorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        if let Some(meta) = self.metadata() {
+            meta.clone_weak();
+        }
+        Self::from_inner(self.ptr)
+    }
+}
+
+impl<META, META2, T> PartialEq for FlexWeak<META, META2, T>
+where
+    META: WeakAlgorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other)
+    }
+}
+
+impl<META, META2, T> Eq for FlexWeak<META, META2, T>
+where
+    META: WeakAlgorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+}
+
+impl<META, META2, T> Hash for FlexWeak<META, META2, T>
+where
+    META: WeakAlgorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.addr().hash(state)
+    }
+}
+
+impl<META, META2, T> Drop for FlexWeak<META, META2, T>
+where
+    META: WeakAlgorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    #[inline]
+    fn drop(&mut self) {
+        let meta = match self.metadata() {
+            Some(meta) => meta,
+            None => return,
+        };
+
+        if meta.drop_weak() {
+            // SAFETY: This was the last reference of any kind and the data was dropped along with
+            // the last strong reference
+            unsafe {
+                dealloc_inner(self.ptr);
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use alloc::vec::Vec;
+
+use crate::{Algorithm, FlexRc, FlexWeak, LocalMeta, SharedMeta, WeakAlgorithm};
+
+// *** WeakList ***
+
+/// A list of weak handles for the observer pattern: registering an observer doesn't keep it alive,
+/// and observers that have been dropped are pruned the next time the list is notified
+pub struct WeakList<META, META2, T>
+where
+    META: WeakAlgorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    weaks: Vec<FlexWeak<META, META2, T>>,
+}
+
+/// A list of `LocalWeak` observers
+pub type LocalWeakList<T> = WeakList<LocalMeta, SharedMeta, T>;
+
+/// A list of `SharedWeak` observers
+pub type SharedWeakList<T> = WeakList<SharedMeta, LocalMeta, T>;
+
+impl<META, META2, T> WeakList<META, META2, T>
+where
+    META: WeakAlgorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    #[inline]
+    pub fn new() -> Self {
+        Self { weaks: Vec::new() }
+    }
+
+    /// Registers a weak handle to `rc`
+    #[inline]
+    pub fn push(&mut self, rc: &FlexRc<META, META2, T>) {
+        self.weaks.push(rc.downgrade());
+    }
+
+    /// Registers an existing weak handle
+    #[inline]
+    pub fn push_weak(&mut self, weak: FlexWeak<META, META2, T>) {
+        self.weaks.push(weak);
+    }
+
+    /// Returns the number of entries, including any dead ones not pruned yet
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.weaks.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.weaks.is_empty()
+    }
+
+    /// Calls `f` on every entry that can still be upgraded, in registration order, and removes the
+    /// dead ones (freeing their allocations if they were the last weak handles). Returns the
+    /// number of entries notified
+    pub fn notify(&mut self, mut f: impl FnMut(&T)) -> usize {
+        self.weaks.retain(|weak| match weak.upgrade() {
+            Some(rc) => {
+                f(&rc.as_inner().data);
+                true
+            }
+            None => false,
+        });
+        self.weaks.len()
+    }
+
+    /// Removes the entries that can no longer be upgraded without notifying the others
+    #[inline]
+    pub fn prune(&mut self) {
+        self.weaks.retain(|weak| weak.strong_count() != 0);
+    }
+}
+
+impl<META, META2, T> Default for WeakList<META, META2, T>
+where
+    META: WeakAlgorithm<META, META2>,
+    META2: Algorithm<META2, META>,
+    T: ?Sized,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,17 @@
+// Usage level checks of the `Send`/`Sync` bounds: each fixture in `ui` must fail to compile with
+// the recorded error. Regenerate the `.stderr` files with `TRYBUILD=overwrite` after an
+// intentional change. Compiler diagnostics change between releases, so the snapshots are only
+// checked on stable (the toolchain they were recorded with)
+
+// Loom atomics can only be used inside a loom model
+#![cfg(not(loom))]
+
+#[rustversion::attr(
+    not(stable),
+    ignore = "stderr snapshots are recorded with stable rustc"
+)]
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
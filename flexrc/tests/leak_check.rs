@@ -0,0 +1,52 @@
+// Leak check counts: a handle that is never dropped (forgotten or kept alive by a cycle) must be
+// counted as live, and freeing it must bring the count back down. The count is global, so this
+// lives in its own test binary where nothing else allocates behind its back
+
+// Loom atomics can only be used inside a loom model, and counting only happens in debug builds
+#![cfg(all(feature = "leak_check", debug_assertions, not(loom)))]
+
+use std::cell::RefCell;
+use std::mem::ManuallyDrop;
+use std::sync::Mutex;
+
+use flexrc::{assert_no_leaks, live_allocations, LocalRc, SharedRc};
+
+// Tests run in parallel, but each one must see only its own allocations
+static SERIAL: Mutex<()> = Mutex::new(());
+
+#[test]
+fn forgotten_handle_is_counted() {
+    let _guard = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    let baseline = live_allocations();
+
+    let mut rc = ManuallyDrop::new(SharedRc::new(5));
+    drop(SharedRc::clone(&rc));
+    assert_eq!(live_allocations(), baseline + 1);
+
+    // SAFETY: `rc` is never used again
+    unsafe { ManuallyDrop::drop(&mut rc) };
+    assert_eq!(live_allocations(), baseline);
+    assert_no_leaks();
+}
+
+#[test]
+fn cycle_is_counted() {
+    struct Node(RefCell<Option<LocalRc<Node>>>);
+
+    let _guard = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    let baseline = live_allocations();
+
+    let a = LocalRc::new(Node(RefCell::new(None)));
+    let b = LocalRc::new(Node(RefCell::new(Some(a.clone()))));
+    *a.0.borrow_mut() = Some(b);
+    let node: *const Node = &*a;
+    drop(a);
+    assert_eq!(live_allocations(), baseline + 2);
+
+    // SAFETY: The cycle keeps the node alive, and the pointer isn't used once it is broken.
+    // Dropping the handle taken out of the cycle frees both nodes
+    let b = unsafe { (*node).0.borrow_mut().take() };
+    drop(b);
+    assert_eq!(live_allocations(), baseline);
+    assert_no_leaks();
+}
@@ -0,0 +1,168 @@
+// Property tests for the slice and str constructors: every constructor must round-trip its input,
+// and cloning/dropping the handles in any order must free each allocation exactly once (checked by
+// a counting global allocator)
+
+// Loom atomics can only be used inside a loom model
+#![cfg(not(loom))]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicIsize, Ordering};
+use std::sync::Mutex;
+
+use flexrc::{SharedRc, SharedStrBuilder};
+use proptest::prelude::*;
+
+// *** Counting allocator ***
+
+struct CountingAlloc;
+
+// Allocations minus deallocations. Signed so a double free shows up as a drop below the baseline
+static LIVE: AtomicIsize = AtomicIsize::new(0);
+
+// The count is global, so cases that measure it must not run concurrently
+static SERIAL: Mutex<()> = Mutex::new(());
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            LIVE.fetch_add(1, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(1, Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // Same allocation count either way: on failure the old block is still live
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+// Runs `f` and asserts it left the allocation count where it found it
+fn assert_balanced(f: impl FnOnce()) {
+    let _guard = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    let before = LIVE.load(Ordering::Relaxed);
+    f();
+    let after = LIVE.load(Ordering::Relaxed);
+
+    // Pooled allocations are kept for reuse instead of being returned to the allocator
+    #[cfg(not(feature = "pool"))]
+    assert_eq!(before, after, "allocations were leaked or freed twice");
+    #[cfg(feature = "pool")]
+    assert!(after >= before, "allocations were freed twice");
+}
+
+// *** Clone/drop sequences ***
+
+#[derive(Clone, Debug)]
+enum Op {
+    Clone(usize),
+    Drop(usize),
+}
+
+fn ops() -> impl Strategy<Value = Vec<Op>> {
+    prop::collection::vec(
+        prop_oneof![
+            any::<prop::sample::Index>().prop_map(|idx| Op::Clone(idx.index(usize::MAX))),
+            any::<prop::sample::Index>().prop_map(|idx| Op::Drop(idx.index(usize::MAX))),
+        ],
+        0..32,
+    )
+}
+
+// Clones and drops `handles` following `ops` (indexes wrap around the live handles), checking the
+// content of every handle still alive along the way. At least one handle is always kept so the
+// content can be checked to the end, after which the rest are dropped in reverse order
+fn exercise<H: Clone>(handle: H, ops: &[Op], expected: &[u8], as_bytes: impl Fn(&H) -> &[u8]) {
+    let mut handles = vec![handle];
+
+    for op in ops {
+        match *op {
+            Op::Clone(idx) => {
+                let clone = handles[idx % handles.len()].clone();
+                handles.push(clone);
+            }
+            Op::Drop(idx) if handles.len() > 1 => {
+                handles.swap_remove(idx % handles.len());
+            }
+            Op::Drop(_) => {}
+        }
+
+        for handle in &handles {
+            assert_eq!(as_bytes(handle), expected);
+        }
+    }
+
+    while handles.pop().is_some() {}
+}
+
+fn as_bytes(rc: &SharedRc<[u8]>) -> &[u8] {
+    rc.as_bytes()
+}
+
+fn as_str_bytes(rc: &SharedRc<str>) -> &[u8] {
+    rc.as_bytes()
+}
+
+// *** Property tests ***
+
+proptest! {
+    #[test]
+    fn str_constructors_round_trip(s in any::<String>(), ops in ops()) {
+        let bytes = s.as_bytes();
+
+        assert_balanced(|| {
+            exercise(SharedRc::<[u8]>::from_str_ref(&s), &ops, bytes, as_bytes);
+            exercise(SharedRc::<[u8]>::try_from_str_ref(&s).unwrap(), &ops, bytes, as_bytes);
+            exercise(SharedRc::<[u8]>::from_utf8(bytes).unwrap(), &ops, bytes, as_bytes);
+            exercise(SharedRc::<[u8]>::from_utf8_lossy(bytes), &ops, bytes, as_bytes);
+            #[cfg(feature = "str_deref")]
+            {
+                exercise(s.chars().collect::<SharedRc<[u8]>>(), &ops, bytes, as_bytes);
+                exercise(s.split_inclusive(' ').collect::<SharedRc<[u8]>>(), &ops, bytes, as_bytes);
+            }
+
+            exercise(SharedRc::<str>::from(s.as_str()), &ops, bytes, as_str_bytes);
+            exercise(SharedRc::<str>::from(s.clone()), &ops, bytes, as_str_bytes);
+            exercise(SharedRc::<str>::from(s.clone().into_boxed_str()), &ops, bytes, as_str_bytes);
+
+            let mut builder = SharedStrBuilder::new();
+            for part in s.split_inclusive(' ') {
+                builder.push_str(part);
+            }
+            exercise(builder.finish(), &ops, bytes, as_str_bytes);
+        });
+    }
+
+    #[cfg(not(feature = "str_deref"))]
+    #[test]
+    fn byte_constructors_round_trip(bytes in any::<Vec<u8>>(), ops in ops()) {
+        assert_balanced(|| {
+            exercise(SharedRc::<[u8]>::from_slice(&bytes), &ops, &bytes, as_bytes);
+            exercise(SharedRc::<[u8]>::from_slice_cloned(&bytes), &ops, &bytes, as_bytes);
+            exercise(SharedRc::<[u8]>::from_vec(bytes.clone()), &ops, &bytes, as_bytes);
+            exercise(
+                SharedRc::<[u8]>::from_boxed_slice(bytes.clone().into_boxed_slice()),
+                &ops,
+                &bytes,
+                as_bytes,
+            );
+
+            let (head, tail) = bytes.split_at(bytes.len() / 2);
+            exercise(SharedRc::<[u8]>::concat(&[head, tail]), &ops, &bytes, as_bytes);
+
+            let mut buf = flexrc::SharedBuf::new();
+            for chunk in bytes.chunks(3) {
+                buf.extend_from_slice(chunk);
+            }
+            exercise(buf.freeze(), &ops, &bytes, as_bytes);
+        });
+    }
+}
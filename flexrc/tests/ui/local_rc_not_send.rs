@@ -0,0 +1,6 @@
+use flexrc::LocalRc;
+
+fn main() {
+    let local = LocalRc::new(1);
+    std::thread::spawn(move || drop(local));
+}